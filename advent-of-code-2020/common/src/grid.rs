@@ -0,0 +1,462 @@
+//! A rectangular grid stored in a single flat vector
+//!
+//! Cells are addressed as `(column, row)`, i.e. `(x, y)`, with `(0, 0)` in the
+//! top left corner and rows growing downwards.
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// Describes why a grid could not be built
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridError {
+    /// A row did not have the same width as the first row
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A character could not be converted into a cell
+    UnknownCell {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GridError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {} has {} cells but {} were expected",
+                line, found, expected
+            ),
+            GridError::UnknownCell {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "unknown cell '{}' at line {}, column {}",
+                found, line, column
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+/// The offsets of the eight cells surrounding a cell
+const NEIGHBOR_OFFSETS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// The offsets of the four cells above, left, right and below a cell
+const ORTHOGONAL_OFFSETS: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from cells listed row by row
+    ///
+    /// Returns `None` if the number of cells is not `width * height`
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Option<Self> {
+        if cells.len() != width * height {
+            return None;
+        }
+
+        Some(Grid {
+            cells,
+            width,
+            height,
+        })
+    }
+
+    /// Builds a grid from a list of rows that must all have the same width
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self, GridError> {
+        let width = rows.first().map_or(0, Vec::len);
+        let height = rows.len();
+        let mut cells = Vec::with_capacity(width * height);
+
+        for (index, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                return Err(GridError::RaggedRow {
+                    line: index + 1,
+                    expected: width,
+                    found: row.len(),
+                });
+            }
+
+            cells.extend(row);
+        }
+
+        Ok(Grid {
+            cells,
+            width,
+            height,
+        })
+    }
+
+    /// Parses one row per line, converting each character with `cell`
+    pub fn from_str<F>(input: &str, mut cell: F) -> Result<Self, GridError>
+    where
+        F: FnMut(char) -> T,
+    {
+        let rows = input
+            .lines()
+            .map(|line| line.chars().map(&mut cell).collect())
+            .collect();
+
+        Self::from_rows(rows)
+    }
+
+    /// Parses one row per line, failing on the first character `cell` rejects
+    ///
+    /// Line and column numbers in errors start at 1
+    pub fn try_from_str<F>(input: &str, mut cell: F) -> Result<Self, GridError>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        let rows = input
+            .lines()
+            .enumerate()
+            .map(|(line, row)| {
+                row.chars()
+                    .enumerate()
+                    .map(|(column, c)| {
+                        cell(c).ok_or(GridError::UnknownCell {
+                            line: line + 1,
+                            column: column + 1,
+                            found: c,
+                        })
+                    })
+                    .collect()
+            })
+            .collect::<Result<Vec<Vec<T>>, GridError>>()?;
+
+        Self::from_rows(rows)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Gets the cell at the position if it is inside the grid
+    pub fn get(&self, column: usize, row: usize) -> Option<&T> {
+        if column < self.width && row < self.height {
+            self.cells.get(row * self.width + column)
+        } else {
+            None
+        }
+    }
+
+    /// Gets a mutable reference to the cell at the position if it is inside the grid
+    pub fn get_mut(&mut self, column: usize, row: usize) -> Option<&mut T> {
+        if column < self.width && row < self.height {
+            self.cells.get_mut(row * self.width + column)
+        } else {
+            None
+        }
+    }
+
+    /// Gets the cell at a signed position, or `None` if it falls off any edge
+    pub fn get_bounded(&self, column: isize, row: isize) -> Option<&T> {
+        if column < 0 || row < 0 {
+            return None;
+        }
+
+        self.get(column as usize, row as usize)
+    }
+
+    /// Gets the cell at a signed position as if the grid repeated in every direction
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid is empty
+    pub fn get_wrapping(&self, column: isize, row: isize) -> &T {
+        let column = column.rem_euclid(self.width as isize) as usize;
+        let row = row.rem_euclid(self.height as isize) as usize;
+
+        &self[(column, row)]
+    }
+
+    /// Gets the cells of a row from left to right
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row is outside of the grid
+    pub fn row(&self, row: usize) -> &[T] {
+        assert!(row < self.height, "row {} is outside of the grid", row);
+
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    /// Iterates over the cells of a column from top to bottom
+    ///
+    /// # Panics
+    ///
+    /// Panics if the column is outside of the grid
+    pub fn column(&self, column: usize) -> impl Iterator<Item = &T> {
+        assert!(
+            column < self.width,
+            "column {} is outside of the grid",
+            column
+        );
+
+        self.cells.iter().skip(column).step_by(self.width)
+    }
+
+    /// Iterates over every row from top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |row| self.row(row))
+    }
+
+    /// Iterates over every column from left to right
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |column| self.column(column))
+    }
+
+    /// Iterates over every cell along with its position, row by row
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let width = self.width;
+
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(index, cell)| ((index % width, index / width), cell))
+    }
+
+    /// Iterates over the up to eight cells surrounding a position
+    pub fn neighbors(
+        &self,
+        column: usize,
+        row: usize,
+    ) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.neighbors_by(column, row, &NEIGHBOR_OFFSETS)
+    }
+
+    /// Iterates over the up to four cells directly above, below, left and right of a position
+    pub fn orthogonal_neighbors(
+        &self,
+        column: usize,
+        row: usize,
+    ) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.neighbors_by(column, row, &ORTHOGONAL_OFFSETS)
+    }
+
+    fn neighbors_by<'a>(
+        &'a self,
+        column: usize,
+        row: usize,
+        offsets: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = ((usize, usize), &'a T)> {
+        offsets.iter().filter_map(move |(dx, dy)| {
+            let x = column as isize + dx;
+            let y = row as isize + dy;
+
+            self.get_bounded(x, y)
+                .map(|cell| ((x as usize, y as usize), cell))
+        })
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (column, row): (usize, usize)) -> &T {
+        self.get(column, row).unwrap_or_else(|| {
+            panic!(
+                "position ({}, {}) is outside of the {}x{} grid",
+                column, row, self.width, self.height
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (column, row): (usize, usize)) -> &mut T {
+        let (width, height) = (self.width, self.height);
+
+        self.get_mut(column, row).unwrap_or_else(|| {
+            panic!(
+                "position ({}, {}) is outside of the {}x{} grid",
+                column, row, width, height
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_grid() -> Grid<char> {
+        Grid::from_str("abc\ndef\nghi\njkl", |c| c).unwrap()
+    }
+
+    #[test]
+    fn test_from_str() {
+        let grid = example_grid();
+
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 4);
+        assert_eq!(grid[(0, 0)], 'a');
+        assert_eq!(grid[(2, 3)], 'l');
+    }
+
+    #[test]
+    fn test_from_str_with_mapping() {
+        let grid = Grid::from_str(".#\r\n#.\r\n", |c| c == '#').unwrap();
+
+        assert_eq!(
+            grid,
+            Grid::new(2, 2, vec![false, true, true, false]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_ragged_rows() {
+        assert_eq!(
+            Grid::from_str("abc\nde\nfgh", |c| c),
+            Err(GridError::RaggedRow {
+                line: 2,
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            Grid::from_rows(vec![vec![1], vec![]])
+                .unwrap_err()
+                .to_string(),
+            "line 2 has 0 cells but 1 were expected"
+        );
+    }
+
+    #[test]
+    fn test_try_from_str() {
+        let cell = |c| match c {
+            '.' => Some(false),
+            '#' => Some(true),
+            _ => None,
+        };
+
+        assert_eq!(
+            Grid::try_from_str("#.\n.#", cell).unwrap().row(1),
+            &[false, true]
+        );
+        assert_eq!(
+            Grid::try_from_str("#.\n.x", cell),
+            Err(GridError::UnknownCell {
+                line: 2,
+                column: 2,
+                found: 'x'
+            })
+        );
+        assert!(matches!(
+            Grid::try_from_str("#.\n.", cell),
+            Err(GridError::RaggedRow { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_new_checks_size() {
+        assert!(Grid::new(2, 2, vec![1, 2, 3]).is_none());
+        assert!(Grid::<u8>::new(0, 0, vec![]).is_some());
+    }
+
+    #[test]
+    fn test_get_outside_of_grid() {
+        let grid = example_grid();
+
+        assert_eq!(grid.get(2, 1), Some(&'f'));
+        assert_eq!(grid.get(3, 1), None);
+        assert_eq!(grid.get(0, 4), None);
+        assert_eq!(grid.get_bounded(-1, 0), None);
+        assert_eq!(grid.get_bounded(1, 1), Some(&'e'));
+    }
+
+    #[test]
+    fn test_get_wrapping() {
+        let grid = example_grid();
+
+        assert_eq!(grid.get_wrapping(3, 0), &'a');
+        assert_eq!(grid.get_wrapping(-1, 0), &'c');
+        assert_eq!(grid.get_wrapping(4, -1), &'k');
+        assert_eq!(grid.get_wrapping(302, 41), &'f');
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut grid = example_grid();
+        grid[(1, 1)] = 'x';
+        *grid.get_mut(0, 0).unwrap() = 'y';
+
+        assert_eq!(grid.row(0), &['y', 'b', 'c']);
+        assert_eq!(grid.row(1), &['d', 'x', 'f']);
+        assert!(grid.get_mut(5, 5).is_none());
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = example_grid();
+
+        let rows: Vec<String> = grid.rows().map(|row| row.iter().collect()).collect();
+        assert_eq!(rows, vec!["abc", "def", "ghi", "jkl"]);
+
+        let columns: Vec<String> = grid.columns().map(|column| column.collect()).collect();
+        assert_eq!(columns, vec!["adgj", "behk", "cfil"]);
+    }
+
+    #[test]
+    fn test_iter_positions() {
+        let grid = example_grid();
+        let positions: Vec<(usize, usize)> = grid
+            .iter()
+            .filter(|(_, &c)| c == 'e' || c == 'l')
+            .map(|(position, _)| position)
+            .collect();
+
+        assert_eq!(positions, vec![(1, 1), (2, 3)]);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = example_grid();
+
+        let corner: String = grid.neighbors(0, 0).map(|(_, c)| c).collect();
+        assert_eq!(corner, "bde");
+
+        let middle: String = grid.neighbors(1, 1).map(|(_, c)| c).collect();
+        assert_eq!(middle, "abcdfghi");
+
+        let orthogonal: Vec<((usize, usize), &char)> = grid.orthogonal_neighbors(2, 3).collect();
+        assert_eq!(orthogonal, vec![((2, 2), &'i'), ((1, 3), &'k')]);
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the 3x4 grid")]
+    fn test_index_outside_of_grid() {
+        let grid = example_grid();
+        let _ = grid[(3, 0)];
+    }
+}
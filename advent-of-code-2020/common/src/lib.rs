@@ -0,0 +1,4 @@
+//! Helpers shared between the puzzles of more than one day
+
+pub mod grid;
+pub mod records;
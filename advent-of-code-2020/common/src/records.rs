@@ -0,0 +1,167 @@
+//! Records made of lines and separated by blank lines
+//!
+//! Several puzzles group their input this way, such as the passports of day 4
+//! and the customs answers of day 6. Lines may end in `\n` or `\r\n`, any line
+//! containing only whitespace separates records, and surrounding whitespace is
+//! trimmed from every line. Runs of separators, including those at the start
+//! or end of the input, never produce empty records.
+
+use std::fmt;
+use std::iter::Enumerate;
+use std::str::Lines;
+
+/// Describes why the fields of a record could not be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordError {
+    /// A field was not written as `key:value`
+    MissingSeparator { line: usize, field: String },
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordError::MissingSeparator { line, field } => write!(
+                f,
+                "field '{}' on line {} should be written as key:value",
+                field, line
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+/// A group of consecutive non-blank lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record<'a> {
+    /// The line number of the first line, counting from 1
+    pub line: usize,
+    /// Every line of the record with surrounding whitespace removed
+    pub lines: Vec<&'a str>,
+}
+
+impl<'a> Record<'a> {
+    /// Splits the record into `key:value` fields separated by any whitespace
+    ///
+    /// Only the first `:` separates the key, so values may contain `:` themselves
+    pub fn fields(&self) -> Result<Vec<(&'a str, &'a str)>, RecordError> {
+        let mut fields = Vec::new();
+
+        for (offset, line) in self.lines.iter().enumerate() {
+            for field in line.split_whitespace() {
+                let pair = field
+                    .split_once(':')
+                    .ok_or_else(|| RecordError::MissingSeparator {
+                        line: self.line + offset,
+                        field: field.to_owned(),
+                    })?;
+
+                fields.push(pair);
+            }
+        }
+
+        Ok(fields)
+    }
+}
+
+/// An iterator over the records of some text, created by `records`
+#[derive(Debug, Clone)]
+pub struct Records<'a> {
+    lines: Enumerate<Lines<'a>>,
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = Record<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, first) = self
+            .lines
+            .by_ref()
+            .map(|(index, line)| (index, line.trim()))
+            .find(|(_, line)| !line.is_empty())?;
+
+        let mut lines = vec![first];
+        for (_, line) in self.lines.by_ref() {
+            let line = line.trim();
+            if line.is_empty() {
+                break;
+            }
+
+            lines.push(line);
+        }
+
+        Some(Record {
+            line: index + 1,
+            lines,
+        })
+    }
+}
+
+/// Splits text into records at blank or whitespace-only lines
+pub fn records(input: &str) -> Records<'_> {
+    Records {
+        lines: input.lines().enumerate(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records() {
+        let input = "abc\n\na\nb\nc\n\nab\nac\n";
+        let records: Vec<Vec<&str>> = records(input).map(|r| r.lines).collect();
+
+        assert_eq!(
+            records,
+            vec![vec!["abc"], vec!["a", "b", "c"], vec!["ab", "ac"]]
+        );
+    }
+
+    #[test]
+    fn test_separators() {
+        let input = "\r\n  \r\na b\r\n\t c\r\n \t \r\n\r\n\r\nd\r\n\n\n";
+        let records: Vec<Record> = records(input).collect();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].line, 3);
+        assert_eq!(records[0].lines, vec!["a b", "c"]);
+        assert_eq!(records[1].line, 8);
+        assert_eq!(records[1].lines, vec!["d"]);
+
+        assert_eq!(super::records("").count(), 0);
+        assert_eq!(super::records("\n \n\t\n").count(), 0);
+    }
+
+    #[test]
+    fn test_fields() {
+        let input = "ecl:gry  pid:860033327\teyr:2020\r\nurl:http://x:80 empty:\n\nhcl:#fff";
+        let records: Vec<Record> = records(input).collect();
+
+        assert_eq!(
+            records[0].fields(),
+            Ok(vec![
+                ("ecl", "gry"),
+                ("pid", "860033327"),
+                ("eyr", "2020"),
+                ("url", "http://x:80"),
+                ("empty", ""),
+            ])
+        );
+        assert_eq!(records[1].fields(), Ok(vec![("hcl", "#fff")]));
+    }
+
+    #[test]
+    fn test_fields_without_separator() {
+        let input = "\n\nbyr:1937\niyr:2017 cid\n";
+
+        assert_eq!(
+            records(input).next().unwrap().fields(),
+            Err(RecordError::MissingSeparator {
+                line: 4,
+                field: "cid".to_owned()
+            })
+        );
+    }
+}
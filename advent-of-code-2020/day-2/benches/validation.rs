@@ -0,0 +1,120 @@
+use std::fs;
+use std::path::PathBuf;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use day_2::{bulk, parse_input, puzzle_one_solution, puzzle_two_solution};
+
+/// The validators as they were before the byte-oriented fast path
+mod baseline {
+    pub fn validate_by_frequency(policy: &(u32, u32, char), password: &str) -> bool {
+        let mut character_count = 0;
+
+        for c in password.chars() {
+            if c == policy.2 {
+                character_count += 1;
+            }
+        }
+
+        policy.0 <= character_count && character_count <= policy.1
+    }
+
+    pub fn validate_by_position(policy: &(u32, u32, char), password: &str) -> bool {
+        let characters: Vec<char> = password.chars().collect();
+
+        (characters[policy.0 as usize - 1] == policy.2)
+            ^ (characters[policy.1 as usize - 1] == policy.2)
+    }
+}
+
+/// Builds well-formed entries from a fixed linear congruential sequence
+fn generate_entries(count: usize) -> Vec<((u32, u32, char), String)> {
+    let mut state: u64 = 0x2020;
+    let mut next = move |bound: u64| {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 33) % bound
+    };
+
+    (0..count)
+        .map(|_| {
+            let length = 8 + next(24) as u32;
+            let low = 1 + next(length as u64) as u32;
+            let high = low + next((length - low + 1) as u64) as u32;
+            let character = (b'a' + next(26) as u8) as char;
+            let password = (0..length)
+                .map(|_| (b'a' + next(26) as u8) as char)
+                .collect();
+
+            ((low, high, character), password)
+        })
+        .collect()
+}
+
+fn write_input_file(entries: &[((u32, u32, char), String)]) -> PathBuf {
+    let path = std::env::temp_dir().join("day-2-validation-bench.txt");
+    let contents: String = entries
+        .iter()
+        .map(|((low, high, character), password)| {
+            format!("{}-{} {}: {}\n", low, high, character, password)
+        })
+        .collect();
+
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+fn validators(c: &mut Criterion) {
+    let entries = generate_entries(10_000);
+    let mut group = c.benchmark_group("validators");
+
+    group.bench_function("baseline", |b| {
+        b.iter(|| {
+            entries
+                .iter()
+                .filter(|(policy, password)| {
+                    baseline::validate_by_frequency(policy, password)
+                        ^ baseline::validate_by_position(policy, password)
+                })
+                .count()
+        })
+    });
+
+    group.bench_function("bytes", |b| {
+        b.iter(|| {
+            entries
+                .iter()
+                .filter(|(policy, password)| {
+                    day_2::validate_by_frequency(policy, password)
+                        ^ day_2::validate_by_position(policy, password)
+                })
+                .count()
+        })
+    });
+
+    group.finish();
+}
+
+fn whole_file(c: &mut Criterion) {
+    let path = write_input_file(&generate_entries(200_000));
+    let filename = path.to_str().unwrap();
+    let mut group = c.benchmark_group("whole_file");
+    group.sample_size(20);
+
+    group.bench_function("parse_input", |b| {
+        b.iter(|| {
+            let input = parse_input(black_box(filename));
+            (puzzle_one_solution(&input), puzzle_two_solution(&input))
+        })
+    });
+
+    group.bench_function("bulk", |b| {
+        b.iter(|| bulk::validate_file(black_box(filename)).unwrap())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, validators, whole_file);
+criterion_main!(benches);
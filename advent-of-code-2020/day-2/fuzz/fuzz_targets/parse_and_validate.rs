@@ -0,0 +1,25 @@
+//! Feeds arbitrary bytes through every day-2 parser and validator
+//!
+//! Run with `cargo fuzz run parse_and_validate` from the `day-2` directory.
+
+#![no_main]
+
+use day_2::{bulk, parse_entry, policy, validate_by_frequency, validate_by_position};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    bulk::validate_bytes(data);
+
+    if let Ok(input) = std::str::from_utf8(data) {
+        for line in input.lines() {
+            if let Some((policy, password)) = parse_entry(line) {
+                validate_by_frequency(&policy, password);
+                validate_by_position(&policy, password);
+            }
+
+            if let Ok((policy, password)) = policy::parse_line(line) {
+                policy.evaluate(&password);
+            }
+        }
+    }
+});
@@ -0,0 +1,100 @@
+//! Bulk validation over memory-mapped password files
+//!
+//! Lines are validated straight out of the mapped bytes, so no entry is ever
+//! copied into an owned `String`. This is meant for files with millions of
+//! entries where `parse_input` would spend most of its time allocating.
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::{parse_entry, validate_by_frequency, validate_by_position};
+
+/// Totals collected while validating a batch of entries
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BulkSummary {
+    pub entries: usize,
+    pub valid_by_frequency: usize,
+    pub valid_by_position: usize,
+    pub malformed: usize,
+}
+
+/// Validates every line in the buffer under both strategies
+///
+/// Blank lines are skipped, and lines that are not valid UTF-8 or do not follow
+/// the puzzle format are counted as malformed instead of aborting the batch
+pub fn validate_bytes(input: &[u8]) -> BulkSummary {
+    let mut summary = BulkSummary::default();
+
+    for line in input.split(|&b| b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+        if line.is_empty() {
+            continue;
+        }
+
+        summary.entries += 1;
+
+        let entry = std::str::from_utf8(line).ok().and_then(parse_entry);
+
+        match entry {
+            Some((policy, password)) => {
+                if validate_by_frequency(&policy, password) {
+                    summary.valid_by_frequency += 1;
+                }
+                if validate_by_position(&policy, password) {
+                    summary.valid_by_position += 1;
+                }
+            }
+            None => summary.malformed += 1,
+        }
+    }
+
+    summary
+}
+
+/// Memory-maps the file and validates every entry in it
+pub fn validate_file<P>(filename: P) -> io::Result<BulkSummary>
+where
+    P: AsRef<Path>,
+{
+    let file = File::open(filename)?;
+
+    // SAFETY: the map is only read for the duration of this call. Truncating
+    // the file from another process while it is mapped is not supported.
+    let map = unsafe { Mmap::map(&file)? };
+
+    Ok(validate_bytes(&map))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_bytes_example_input() {
+        let input = b"1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n";
+
+        assert_eq!(
+            validate_bytes(input),
+            BulkSummary {
+                entries: 3,
+                valid_by_frequency: 2,
+                valid_by_position: 1,
+                malformed: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_bytes_malformed_lines() {
+        let input = b"1-3 a: abcde\r\n\r\nnot a policy\n1-3 \xff: abc";
+        let summary = validate_bytes(input);
+
+        assert_eq!(summary.entries, 3);
+        assert_eq!(summary.malformed, 2);
+        assert_eq!(summary.valid_by_frequency, 1);
+    }
+}
@@ -0,0 +1,158 @@
+//! Random but well-formed password database entries
+//!
+//! Each entry is built backwards from the validity it should have under both
+//! strategies, so the expected answers are known without running the validators.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+/// A generated `a-b c: password` line along with its expected validity
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedEntry {
+    pub line: String,
+    pub valid_by_frequency: bool,
+    pub valid_by_position: bool,
+}
+
+const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+
+/// Picks a letter from the alphabet other than `excluded`
+fn other_letter<R: Rng>(rng: &mut R, excluded: char) -> char {
+    loop {
+        let letter = *ALPHABET.choose(rng).unwrap() as char;
+        if letter != excluded {
+            return letter;
+        }
+    }
+}
+
+/// Generates a single entry with the requested validity under each strategy
+pub fn generate_entry<R: Rng>(
+    rng: &mut R,
+    valid_by_frequency: bool,
+    valid_by_position: bool,
+) -> GeneratedEntry {
+    loop {
+        let length = rng.gen_range(2..=20);
+        let low = rng.gen_range(1..length);
+        let high = rng.gen_range(low + 1..=length);
+        let character = *ALPHABET.choose(rng).unwrap() as char;
+
+        // Occurrences of the character at the two policy positions
+        let fixed = if valid_by_position {
+            1
+        } else if rng.gen_bool(0.5) {
+            0
+        } else {
+            2
+        };
+
+        // Totals that are reachable by adding the character elsewhere
+        let free = length - 2;
+        let totals: Vec<usize> = (fixed..=fixed + free)
+            .filter(|total| (low..=high).contains(total) == valid_by_frequency)
+            .collect();
+
+        let total = match totals.choose(rng) {
+            Some(&total) => total,
+            None => continue,
+        };
+
+        let mut password: Vec<char> = (0..length).map(|_| other_letter(rng, character)).collect();
+
+        match fixed {
+            1 if rng.gen_bool(0.5) => password[low - 1] = character,
+            1 => password[high - 1] = character,
+            2 => {
+                password[low - 1] = character;
+                password[high - 1] = character;
+            }
+            _ => {}
+        }
+
+        let mut others: Vec<usize> = (0..length)
+            .filter(|&index| index != low - 1 && index != high - 1)
+            .collect();
+        others.shuffle(rng);
+
+        for &index in others.iter().take(total - fixed) {
+            password[index] = character;
+        }
+
+        let password: String = password.into_iter().collect();
+
+        return GeneratedEntry {
+            line: format!("{}-{} {}: {}", low, high, character, password),
+            valid_by_frequency,
+            valid_by_position,
+        };
+    }
+}
+
+/// Generates `count` entries from a seed with every combination of validity
+pub fn generate_entries(seed: u64, count: usize) -> Vec<GeneratedEntry> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    (0..count)
+        .map(|_| {
+            let valid_by_frequency = rng.gen_bool(0.5);
+            let valid_by_position = rng.gen_bool(0.5);
+            generate_entry(&mut rng, valid_by_frequency, valid_by_position)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bulk, parse_entry, validate_by_frequency, validate_by_position};
+
+    #[test]
+    fn test_generated_entries_match_validators() {
+        for entry in generate_entries(2020, 5_000) {
+            let (policy, password) = parse_entry(&entry.line).unwrap();
+
+            assert_eq!(
+                validate_by_frequency(&policy, password),
+                entry.valid_by_frequency,
+                "{}",
+                entry.line
+            );
+            assert_eq!(
+                validate_by_position(&policy, password),
+                entry.valid_by_position,
+                "{}",
+                entry.line
+            );
+        }
+    }
+
+    #[test]
+    fn test_generated_batch_counts() {
+        let entries = generate_entries(7, 1_000);
+        let input: String = entries
+            .iter()
+            .map(|entry| entry.line.clone() + "\n")
+            .collect();
+        let summary = bulk::validate_bytes(input.as_bytes());
+
+        let frequency = entries
+            .iter()
+            .filter(|entry| entry.valid_by_frequency)
+            .count();
+        let position = entries
+            .iter()
+            .filter(|entry| entry.valid_by_position)
+            .count();
+
+        assert_eq!(summary.malformed, 0);
+        assert_eq!(summary.valid_by_frequency, frequency);
+        assert_eq!(summary.valid_by_position, position);
+    }
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        assert_eq!(generate_entries(42, 100), generate_entries(42, 100));
+    }
+}
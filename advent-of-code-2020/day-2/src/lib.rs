@@ -0,0 +1,273 @@
+use std::fs::File;
+use std::io::{self, BufRead};
+use std::path::Path;
+
+pub mod bulk;
+pub mod generator;
+pub mod policy;
+pub mod stream;
+
+use policy::Policy;
+
+/// Creates an iterator over the lines of the puzzle input
+fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
+where
+    P: AsRef<Path>,
+{
+    let file = File::open(filename)?;
+    Ok(io::BufReader::new(file).lines())
+}
+
+/// Parses input into a vector of tuples
+///
+/// The first entry is the policy: a tuple containing the two numbers and character
+/// The second entry is the password
+pub fn parse_input(filename: &str) -> Vec<((u32, u32, char), String)> {
+    let mut results = Vec::new();
+
+    match read_lines(filename) {
+        Ok(lines) => {
+            for line in lines {
+                let line = line.unwrap();
+                let line: Vec<&str> = line.split(':').collect();
+
+                let policy: Vec<&str> = line[0].split(' ').collect();
+
+                let frequencies: Vec<u32> = policy[0]
+                    .split('-')
+                    .map(|val| val.parse::<u32>().unwrap())
+                    .collect();
+
+                let character = policy[1].chars().next().unwrap();
+
+                let password = line[1].trim().to_owned();
+
+                results.push(((frequencies[0], frequencies[1], character), password));
+            }
+        }
+        Err(e) => panic!("Error reading file: {}", e),
+    };
+
+    results
+}
+
+/// Parses input written in the extended policy grammar
+///
+/// Each line is a `<policy>: <password>` pair, see the `policy` module for the grammar
+pub fn parse_extended_input(filename: &str) -> Vec<(Policy, String)> {
+    let mut results = Vec::new();
+
+    match read_lines(filename) {
+        Ok(lines) => {
+            for (number, line) in lines.enumerate() {
+                let line = line.unwrap();
+
+                match policy::parse_line(&line) {
+                    Ok(entry) => results.push(entry),
+                    Err(e) => panic!("Error parsing line {}: {}", number + 1, e),
+                }
+            }
+        }
+        Err(e) => panic!("Error reading file: {}", e),
+    };
+
+    results
+}
+
+/// Helps to determine which validation method to use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    Frequency,
+    Position,
+}
+
+/// Validates a password using the policy as a frequency range
+///
+/// An ASCII policy letter is counted byte by byte, which the compiler can
+/// vectorize. No byte of a multi-byte character is ASCII, so this gives the same
+/// count as comparing `char`s, which is only needed for other letters.
+pub fn validate_by_frequency(policy: &(u32, u32, char), password: &str) -> bool {
+    let character_count = if policy.2.is_ascii() {
+        let byte = policy.2 as u8;
+        password.bytes().filter(|&b| b == byte).count()
+    } else {
+        password.chars().filter(|&c| c == policy.2).count()
+    };
+
+    policy.0 as usize <= character_count && character_count <= policy.1 as usize
+}
+
+/// Finds the character at a 1-based position in the password without allocating
+///
+/// Only the bytes up to the position are checked for ASCII, since a byte is a
+/// whole character as long as every byte before it is ASCII
+pub(crate) fn character_at(password: &str, position: u32) -> Option<char> {
+    let index = (position as usize).checked_sub(1)?;
+    let bytes = password.as_bytes();
+    let prefix = &bytes[..bytes.len().min(index + 1)];
+
+    if prefix.is_ascii() {
+        bytes.get(index).map(|&b| b as char)
+    } else {
+        password.chars().nth(index)
+    }
+}
+
+/// Validates a password using the policy as indices in the password
+///
+/// Positions that fall outside of the password never match the character
+pub fn validate_by_position(policy: &(u32, u32, char), password: &str) -> bool {
+    (character_at(password, policy.0) == Some(policy.2))
+        ^ (character_at(password, policy.1) == Some(policy.2))
+}
+
+/// Parses a single `a-b c: password` line without allocating
+///
+/// Returns `None` if the line does not follow the puzzle format
+pub fn parse_entry(line: &str) -> Option<((u32, u32, char), &str)> {
+    let (policy, password) = line.split_once(':')?;
+    let (range, character) = policy.split_once(' ')?;
+    let (low, high) = range.split_once('-')?;
+
+    let mut characters = character.chars();
+    let character = characters.next()?;
+    if characters.next().is_some() {
+        return None;
+    }
+
+    Some((
+        (low.parse().ok()?, high.parse().ok()?, character),
+        password.trim(),
+    ))
+}
+
+/// Determines if a password is valid given a policy and a validation strategy
+/// 
+/// If the strategy is `Frequency` then the policy is interpreted as frequency ranges.
+/// If the strategy is `Position` then the policy is treated is indices into the password
+pub fn is_password_valid(policy: &(u32, u32, char), password: &str, strategy: Strategy) -> bool {    
+    match strategy {
+        Strategy::Frequency => validate_by_frequency(policy, password),
+        Strategy::Position => validate_by_position(policy, password),
+    }
+}
+
+/// Counts how many passwords are valid using a frequency validation strategy
+pub fn puzzle_one_solution(input: &[((u32, u32, char), String)]) -> u32 {
+    let mut valid_count = 0;
+
+    for (policy, password) in input {
+        if is_password_valid(policy, password, Strategy::Frequency) {
+            valid_count += 1;
+        }
+    }
+
+    valid_count
+}
+
+/// Counts how many passwords are valid using a position validation strategy
+pub fn puzzle_two_solution(input: &[((u32, u32, char), String)]) -> u32 {
+    let mut valid_count = 0;
+
+    for (policy, password) in input {
+        if is_password_valid(policy, password, Strategy::Position) {
+            valid_count += 1;
+        }
+    }
+
+    valid_count
+}
+
+/// Counts how many passwords satisfy their extended policies
+pub fn count_valid_extended(input: &[(Policy, String)]) -> u32 {
+    input
+        .iter()
+        .filter(|(policy, password)| policy.evaluate(password))
+        .count() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_puzzle_one_example_input() {
+        let input = vec![
+            ((1, 3, 'a'), "abcde".to_owned()),
+            ((1, 3, 'b'), "cdefg".to_owned()),
+            ((2, 9, 'c'), "ccccccccc".to_owned()),
+        ];
+
+        assert_eq!(puzzle_one_solution(&input), 2);
+    }
+
+    #[test]
+    fn test_puzzle_one_example_bad_input() {
+        let input = vec![];
+
+        assert_eq!(puzzle_one_solution(&input), 0);
+    }
+
+    #[test]
+    fn test_puzzle_two_example_input() {
+        let input = vec![
+            ((1, 3, 'a'), "abcde".to_owned()),
+            ((1, 3, 'b'), "cdefg".to_owned()),
+            ((2, 9, 'c'), "ccccccccc".to_owned()),
+        ];
+
+        assert_eq!(puzzle_two_solution(&input), 1);
+    }
+
+    #[test]
+    fn test_puzzle_two_example_bad_input() {
+        let input = vec![];
+
+        assert_eq!(puzzle_two_solution(&input), 0);
+    }
+
+    #[test]
+    fn test_extended_example_input() {
+        let lines = [
+            "1-3 a: abcde",
+            "1-3 a, 1-1 b: abcde",
+            "1-3 b; pos 1|3 a: abcde",
+        ];
+        let input: Vec<(Policy, String)> = lines
+            .iter()
+            .map(|line| policy::parse_line(line).unwrap())
+            .collect();
+
+        assert_eq!(count_valid_extended(&input), 3);
+    }
+
+    #[test]
+    fn test_non_ascii_passwords() {
+        assert!(validate_by_frequency(&(2, 2, 'é'), "héllé"));
+        assert!(!validate_by_frequency(&(1, 3, 'é'), "hello"));
+        assert!(validate_by_frequency(&(2, 2, 'l'), "héllo"));
+        assert!(!validate_by_frequency(&(1, 9, 'e'), "héééé"));
+        assert!(validate_by_position(&(2, 5, 'é'), "héllo"));
+        assert!(!validate_by_position(&(2, 5, 'é'), "héllé"));
+        assert!(validate_by_position(&(2, 3, 'a'), "éa€a"));
+        assert!(validate_by_position(&(1, 3, 'a'), "abcé"));
+        assert!(validate_by_position(&(2, 4, 'é'), "ab€é"));
+        assert!(!validate_by_position(&(4, 9, 'é'), "abc"));
+        assert_eq!(character_at("ab€é", 4), Some('é'));
+        assert_eq!(character_at("ab€é", 5), None);
+    }
+
+    #[test]
+    fn test_position_outside_password() {
+        assert!(!validate_by_position(&(0, 9, 'a'), "abc"));
+        assert!(validate_by_position(&(1, 9, 'a'), "abc"));
+    }
+
+    #[test]
+    fn test_parse_entry() {
+        assert_eq!(parse_entry("1-3 a: abcde"), Some(((1, 3, 'a'), "abcde")));
+        assert_eq!(parse_entry("1-3 ab: abcde"), None);
+        assert_eq!(parse_entry("1-x a: abcde"), None);
+        assert_eq!(parse_entry("1-3 a abcde"), None);
+    }
+}
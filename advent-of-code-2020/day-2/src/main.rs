@@ -1,156 +1,93 @@
 use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+use std::io::{self, BufReader, Write};
 
-/// Creates an iterator over the lines of the puzzle input
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
-}
-
-/// Parses input into a vector of tuples
-///
-/// The first entry is the policy: a tuple containing the two numbers and character
-/// The second entry is the password
-fn parse_input(filename: &str) -> Vec<((u32, u32, char), String)> {
-    let mut results = Vec::new();
-
-    match read_lines(filename) {
-        Ok(lines) => {
-            for line in lines {
-                let line = line.unwrap();
-                let line: Vec<&str> = line.split(':').collect();
+use day_2::stream::{self, StreamOptions};
+use day_2::{
+    bulk, count_valid_extended, generator, parse_extended_input, parse_input, puzzle_one_solution,
+    puzzle_two_solution, Strategy,
+};
 
-                let policy: Vec<&str> = line[0].split(' ').collect();
-
-                let frequencies: Vec<u32> = policy[0]
-                    .split('-')
-                    .map(|val| val.parse::<u32>().unwrap())
-                    .collect();
-
-                let character = policy[1].chars().next().unwrap();
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let filename = args.get(1).map_or("./puzzle_input.txt", String::as_str);
 
-                let password = line[1].trim().to_owned();
+    match args.first().map(String::as_str) {
+        Some("--extended") => {
+            let input = parse_extended_input(filename);
 
-                results.push(((frequencies[0], frequencies[1], character), password));
-            }
+            println!(
+                "Extended Policy Solution -> {}",
+                count_valid_extended(&input)
+            );
         }
-        Err(e) => panic!("Error reading file: {}", e),
-    };
-
-    results
-}
-
-/// Helps to determine which validation method to use
-enum Strategy {
-    Frequency,
-    Position,
-}
-
-/// Validates a password using the policy as a frequency range
-fn validate_by_frequency(policy: &(u32, u32, char), password: &str) -> bool {
-    let mut character_count = 0;
+        Some("--bulk") => match bulk::validate_file(filename) {
+            Ok(summary) => {
+                println!("Puzzle 1 Solution -> {}", summary.valid_by_frequency);
+                println!("Puzzle 2 Solution -> {}", summary.valid_by_position);
+                println!("Malformed Entries -> {}", summary.malformed);
+            }
+            Err(e) => panic!("Error reading file: {}", e),
+        },
+        Some("--generate") => {
+            let count = args.get(1).map_or(1000, |count| count.parse().unwrap());
+            let seed = args.get(2).map_or(2020, |seed| seed.parse().unwrap());
+            let entries = generator::generate_entries(seed, count);
+
+            for entry in &entries {
+                println!("{}", entry.line);
+            }
 
-    for c in password.chars() {
-        if c == policy.2 {
-            character_count += 1;
+            let frequency = entries.iter().filter(|e| e.valid_by_frequency).count();
+            let position = entries.iter().filter(|e| e.valid_by_position).count();
+            eprintln!("Expected Puzzle 1 Solution -> {}", frequency);
+            eprintln!("Expected Puzzle 2 Solution -> {}", position);
         }
-    }
-
-    policy.0 <= character_count && character_count <= policy.1
-}
-
-/// Validates a password using the policy as indices in the password
-fn validate_by_position(policy: &(u32, u32, char), password: &str) -> bool {
-    let characters: Vec<char> = password.chars().collect();
-
-    (characters[policy.0 as usize - 1] == policy.2) ^ (characters[policy.1 as usize - 1] == policy.2)
-}
-
-/// Determines if a password is valid given a policy and a validation strategy
-/// 
-/// If the strategy is `Frequency` then the policy is interpreted as frequency ranges.
-/// If the strategy is `Position` then the policy is treated is indices into the password
-fn is_password_valid(policy: &(u32, u32, char), password: &str, strategy: Strategy) -> bool {    
-    match strategy {
-        Strategy::Frequency => validate_by_frequency(policy, password),
-        Strategy::Position => validate_by_position(policy, password),
-    }
-}
+        Some("--stream") => {
+            let filename = args[1..]
+                .iter()
+                .find(|arg| !arg.starts_with("--"))
+                .map_or("./puzzle_input.txt", String::as_str);
+            let options = StreamOptions {
+                strategy: if args.iter().any(|arg| arg == "--position") {
+                    Strategy::Position
+                } else {
+                    Strategy::Frequency
+                },
+                fail_fast: args.iter().any(|arg| arg == "--fail-fast"),
+                ..StreamOptions::default()
+            };
+
+            let file = File::open(filename).unwrap_or_else(|e| panic!("Error reading file: {}", e));
+            let size = file.metadata().map(|m| m.len()).unwrap_or(0).max(1);
+
+            let mut reported = false;
+            let summary = stream::validate_stream(BufReader::new(file), options, |progress| {
+                reported = true;
+                eprint!(
+                    "\rValidated {} lines ({:.1}%)",
+                    progress.lines,
+                    progress.bytes as f64 / size as f64 * 100.0
+                );
+                io::stderr().flush().ok();
+            })
+            .unwrap_or_else(|e| panic!("Error reading file: {}", e));
+            if reported {
+                eprintln!();
+            }
 
-/// Counts how many passwords are valid using a frequency validation strategy
-pub fn puzzle_one_solution(input: &[((u32, u32, char), String)]) -> u32 {
-    let mut valid_count = 0;
+            println!("Valid Entries -> {}", summary.valid);
+            println!("Invalid Entries -> {}", summary.invalid);
 
-    for (policy, password) in input {
-        if is_password_valid(policy, password, Strategy::Frequency) {
-            valid_count += 1;
+            if let (true, Some(line)) = (options.fail_fast, summary.first_invalid) {
+                println!("First Invalid Entry -> line {}", line);
+                std::process::exit(1);
+            }
         }
-    }
-
-    valid_count
-}
-
-/// Counts how many passwords are valid using a position validation strategy
-pub fn puzzle_two_solution(input: &[((u32, u32, char), String)]) -> u32 {
-    let mut valid_count = 0;
+        _ => {
+            let input = parse_input("./puzzle_input.txt");
 
-    for (policy, password) in input {
-        if is_password_valid(policy, password, Strategy::Position) {
-            valid_count += 1;
+            println!("Puzzle 1 Solution -> {}", puzzle_one_solution(&input));
+            println!("Puzzle 2 Solution -> {}", puzzle_two_solution(&input));
         }
     }
-
-    valid_count
-}
-
-fn main() {
-    let input = parse_input("./puzzle_input.txt");
-
-    println!("Puzzle 1 Solution -> {}", puzzle_one_solution(&input));
-    println!("Puzzle 2 Solution -> {}", puzzle_two_solution(&input));
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_puzzle_one_example_input() {
-        let input = vec![
-            ((1, 3, 'a'), "abcde".to_owned()),
-            ((1, 3, 'b'), "cdefg".to_owned()),
-            ((2, 9, 'c'), "ccccccccc".to_owned()),
-        ];
-
-        assert_eq!(puzzle_one_solution(&input), 2);
-    }
-
-    #[test]
-    fn test_puzzle_one_example_bad_input() {
-        let input = vec![];
-
-        assert_eq!(puzzle_one_solution(&input), 0);
-    }
-
-    #[test]
-    fn test_puzzle_two_example_input() {
-        let input = vec![
-            ((1, 3, 'a'), "abcde".to_owned()),
-            ((1, 3, 'b'), "cdefg".to_owned()),
-            ((2, 9, 'c'), "ccccccccc".to_owned()),
-        ];
-
-        assert_eq!(puzzle_two_solution(&input), 1);
-    }
-
-    #[test]
-    fn test_puzzle_two_example_bad_input() {
-        let input = vec![];
-
-        assert_eq!(puzzle_two_solution(&input), 0);
-    }
 }
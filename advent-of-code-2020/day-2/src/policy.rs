@@ -0,0 +1,271 @@
+//! An extended password policy grammar
+//!
+//! A policy is a list of rule sets separated by `;`, and a password is valid
+//! if it satisfies any one of them. Each rule set is a list of clauses separated
+//! by `,`, all of which must hold. Two kinds of clause are supported:
+//!
+//! - `a-b c` requires the character `c` to appear between `a` and `b` times
+//! - `pos i|j|... c` requires `c` at exactly one of the listed positions
+//!
+//! For example `1-3 a, 2-5 b; pos 1|4 x` accepts a password with one to three
+//! `a`s and two to five `b`s, or one with an `x` at exactly one of positions 1 and 4.
+//! The original single clause format (`1-3 a`) is a valid policy on its own.
+
+use std::fmt;
+
+use crate::character_at;
+
+/// A single requirement on a password
+#[derive(Debug, Clone, PartialEq)]
+pub enum Clause {
+    /// The character must appear between `min` and `max` times, inclusive
+    Frequency { min: u32, max: u32, character: char },
+    /// The character must appear at exactly one of the 1-based positions
+    Position {
+        positions: Vec<u32>,
+        character: char,
+    },
+}
+
+/// A group of clauses that must all hold
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleSet {
+    pub clauses: Vec<Clause>,
+}
+
+/// A parsed policy: a password is valid if any rule set holds
+#[derive(Debug, Clone, PartialEq)]
+pub struct Policy {
+    pub alternatives: Vec<RuleSet>,
+}
+
+/// Describes why a policy or line could not be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolicyError {
+    EmptyClause,
+    MissingPassword,
+    InvalidNumber(String),
+    InvalidRange(u32, u32),
+    InvalidCharacter(String),
+    InvalidClause(String),
+    ZeroPosition,
+}
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PolicyError::EmptyClause => write!(f, "empty clause"),
+            PolicyError::MissingPassword => write!(f, "missing ':' before the password"),
+            PolicyError::InvalidNumber(value) => write!(f, "invalid number '{}'", value),
+            PolicyError::InvalidRange(min, max) => write!(f, "invalid range {}-{}", min, max),
+            PolicyError::InvalidCharacter(value) => {
+                write!(f, "expected a single character, found '{}'", value)
+            }
+            PolicyError::InvalidClause(value) => write!(f, "invalid clause '{}'", value),
+            PolicyError::ZeroPosition => write!(f, "positions start at 1"),
+        }
+    }
+}
+
+impl std::error::Error for PolicyError {}
+
+impl Clause {
+    /// Determines if the password satisfies this clause
+    pub fn evaluate(&self, password: &str) -> bool {
+        match self {
+            Clause::Frequency {
+                min,
+                max,
+                character,
+            } => {
+                let count = password.chars().filter(|c| c == character).count() as u32;
+                *min <= count && count <= *max
+            }
+            Clause::Position {
+                positions,
+                character,
+            } => {
+                let matches = positions
+                    .iter()
+                    .filter(|&&position| character_at(password, position) == Some(*character))
+                    .count();
+                matches == 1
+            }
+        }
+    }
+}
+
+impl RuleSet {
+    /// Determines if the password satisfies every clause
+    pub fn evaluate(&self, password: &str) -> bool {
+        self.clauses.iter().all(|clause| clause.evaluate(password))
+    }
+}
+
+impl Policy {
+    /// Determines if the password satisfies any of the rule sets
+    pub fn evaluate(&self, password: &str) -> bool {
+        self.alternatives
+            .iter()
+            .any(|rule_set| rule_set.evaluate(password))
+    }
+}
+
+fn parse_number(value: &str) -> Result<u32, PolicyError> {
+    value
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| PolicyError::InvalidNumber(value.trim().to_owned()))
+}
+
+fn parse_character(value: &str) -> Result<char, PolicyError> {
+    let mut chars = value.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(PolicyError::InvalidCharacter(value.to_owned())),
+    }
+}
+
+/// Parses a single `a-b c` or `pos i|j c` clause
+fn parse_clause(input: &str) -> Result<Clause, PolicyError> {
+    let tokens: Vec<&str> = input.split_whitespace().collect();
+
+    match tokens.as_slice() {
+        [] => Err(PolicyError::EmptyClause),
+        ["pos", positions, character] => {
+            let positions = positions
+                .split('|')
+                .map(parse_number)
+                .collect::<Result<Vec<u32>, PolicyError>>()?;
+
+            if positions.contains(&0) {
+                return Err(PolicyError::ZeroPosition);
+            }
+
+            Ok(Clause::Position {
+                positions,
+                character: parse_character(character)?,
+            })
+        }
+        [range, character] => {
+            let (min, max) = range
+                .split_once('-')
+                .ok_or_else(|| PolicyError::InvalidNumber((*range).to_owned()))?;
+            let (min, max) = (parse_number(min)?, parse_number(max)?);
+
+            if min > max {
+                return Err(PolicyError::InvalidRange(min, max));
+            }
+
+            Ok(Clause::Frequency {
+                min,
+                max,
+                character: parse_character(character)?,
+            })
+        }
+        _ => Err(PolicyError::InvalidClause(input.trim().to_owned())),
+    }
+}
+
+/// Parses a policy written in the extended grammar
+pub fn parse_policy(input: &str) -> Result<Policy, PolicyError> {
+    let alternatives = input
+        .split(';')
+        .map(|rule_set| {
+            let clauses = rule_set
+                .split(',')
+                .map(parse_clause)
+                .collect::<Result<Vec<Clause>, PolicyError>>()?;
+
+            Ok(RuleSet { clauses })
+        })
+        .collect::<Result<Vec<RuleSet>, PolicyError>>()?;
+
+    Ok(Policy { alternatives })
+}
+
+/// Parses a `<policy>: <password>` line
+pub fn parse_line(line: &str) -> Result<(Policy, String), PolicyError> {
+    let (policy, password) = line.split_once(':').ok_or(PolicyError::MissingPassword)?;
+
+    Ok((parse_policy(policy)?, password.trim().to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_single_clause() {
+        let (policy, password) = parse_line("1-3 a: abcde").unwrap();
+
+        assert_eq!(
+            policy.alternatives,
+            vec![RuleSet {
+                clauses: vec![Clause::Frequency {
+                    min: 1,
+                    max: 3,
+                    character: 'a'
+                }]
+            }]
+        );
+        assert_eq!(password, "abcde");
+    }
+
+    #[test]
+    fn test_single_clause_matches_frequency_strategy() {
+        let lines = ["1-3 a: abcde", "1-3 b: cdefg", "2-9 c: ccccccccc"];
+        let valid: Vec<bool> = lines
+            .iter()
+            .map(|line| {
+                let (policy, password) = parse_line(line).unwrap();
+                policy.evaluate(&password)
+            })
+            .collect();
+
+        assert_eq!(valid, vec![true, false, true]);
+    }
+
+    #[test]
+    fn test_multiple_clauses() {
+        let policy = parse_policy("1-3 a, 2-5 b; pos 1|4 x").unwrap();
+
+        assert_eq!(policy.alternatives.len(), 2);
+        assert_eq!(policy.alternatives[0].clauses.len(), 2);
+
+        assert!(policy.evaluate("abb"));
+        assert!(!policy.evaluate("ab"));
+        assert!(policy.evaluate("xyz"));
+        assert!(!policy.evaluate("xyzx"));
+        assert!(!policy.evaluate("yx"));
+    }
+
+    #[test]
+    fn test_position_out_of_bounds_is_not_a_match() {
+        let policy = parse_policy("pos 1|9 c").unwrap();
+
+        assert!(policy.evaluate("cde"));
+        assert!(!policy.evaluate(""));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_line("1-3 a abcde"), Err(PolicyError::MissingPassword));
+        assert_eq!(parse_policy("1-3 a,"), Err(PolicyError::EmptyClause));
+        assert_eq!(parse_policy("3-1 a"), Err(PolicyError::InvalidRange(3, 1)));
+        assert_eq!(parse_policy("pos 0|2 a"), Err(PolicyError::ZeroPosition));
+        assert_eq!(
+            parse_policy("x-3 a"),
+            Err(PolicyError::InvalidNumber("x".to_owned()))
+        );
+        assert_eq!(
+            parse_policy("1-3 ab"),
+            Err(PolicyError::InvalidCharacter("ab".to_owned()))
+        );
+        assert_eq!(
+            parse_policy("1-3 a b"),
+            Err(PolicyError::InvalidClause("1-3 a b".to_owned()))
+        );
+    }
+}
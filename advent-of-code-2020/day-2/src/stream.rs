@@ -0,0 +1,183 @@
+//! Line-by-line validation of password files
+//!
+//! Unlike `parse_input`, entries are validated as soon as they are read and
+//! only one line is held in memory at a time. Lines are read as bytes, so one
+//! that is not UTF-8 is counted as a malformed entry instead of ending the stream.
+
+use std::io::{self, BufRead};
+use std::str;
+
+use crate::{is_password_valid, parse_entry, Strategy};
+
+/// Controls how a stream is validated
+#[derive(Debug, Clone, Copy)]
+pub struct StreamOptions {
+    pub strategy: Strategy,
+    /// Stop reading at the first invalid or malformed entry
+    pub fail_fast: bool,
+    /// Number of lines between progress reports, or zero to never report
+    pub progress_interval: usize,
+}
+
+impl Default for StreamOptions {
+    fn default() -> Self {
+        StreamOptions {
+            strategy: Strategy::Frequency,
+            fail_fast: false,
+            progress_interval: 100_000,
+        }
+    }
+}
+
+/// How far through the stream validation has got
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub lines: usize,
+    pub bytes: u64,
+}
+
+/// Totals collected while validating a stream
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StreamSummary {
+    pub lines: usize,
+    pub valid: usize,
+    pub invalid: usize,
+    /// The 1-based line number of the first invalid or malformed entry
+    pub first_invalid: Option<usize>,
+}
+
+/// Validates each line of the reader as it is read
+///
+/// Malformed lines count as invalid entries. `on_progress` is called every
+/// `progress_interval` lines with the number of lines and bytes consumed so far.
+pub fn validate_stream<R, F>(
+    mut reader: R,
+    options: StreamOptions,
+    mut on_progress: F,
+) -> io::Result<StreamSummary>
+where
+    R: BufRead,
+    F: FnMut(Progress),
+{
+    let mut summary = StreamSummary::default();
+    let mut bytes = 0;
+    let mut line = Vec::new();
+
+    loop {
+        line.clear();
+
+        let read = reader.read_until(b'\n', &mut line)?;
+        if read == 0 {
+            break;
+        }
+
+        bytes += read as u64;
+        summary.lines += 1;
+
+        if options.progress_interval > 0 && summary.lines % options.progress_interval == 0 {
+            on_progress(Progress {
+                lines: summary.lines,
+                bytes,
+            });
+        }
+
+        let valid = match str::from_utf8(&line) {
+            Ok(line) => {
+                let entry = line.trim_end_matches(&['\r', '\n'][..]);
+                if entry.is_empty() {
+                    continue;
+                }
+
+                match parse_entry(entry) {
+                    Some((policy, password)) => {
+                        is_password_valid(&policy, password, options.strategy)
+                    }
+                    None => false,
+                }
+            }
+            Err(_) => false,
+        };
+
+        if valid {
+            summary.valid += 1;
+        } else {
+            summary.invalid += 1;
+            summary.first_invalid.get_or_insert(summary.lines);
+
+            if options.fail_fast {
+                break;
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n";
+
+    #[test]
+    fn test_validate_stream_example_input() {
+        let summary =
+            validate_stream(EXAMPLE.as_bytes(), StreamOptions::default(), |_| {}).unwrap();
+
+        assert_eq!(
+            summary,
+            StreamSummary {
+                lines: 3,
+                valid: 2,
+                invalid: 1,
+                first_invalid: Some(2),
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_stream_fail_fast() {
+        let options = StreamOptions {
+            strategy: Strategy::Position,
+            fail_fast: true,
+            ..StreamOptions::default()
+        };
+        let summary = validate_stream(EXAMPLE.as_bytes(), options, |_| {}).unwrap();
+
+        assert_eq!(summary.lines, 2);
+        assert_eq!(summary.first_invalid, Some(2));
+    }
+
+    #[test]
+    fn test_validate_stream_reports_progress() {
+        let input = EXAMPLE.repeat(4) + "garbage\n";
+        let options = StreamOptions {
+            progress_interval: 5,
+            ..StreamOptions::default()
+        };
+        let mut reports = Vec::new();
+        let summary = validate_stream(input.as_bytes(), options, |p| reports.push(p)).unwrap();
+
+        assert_eq!(summary.first_invalid, Some(2));
+        assert_eq!(summary.invalid, 5);
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].lines, 5);
+        assert_eq!(reports[1].bytes, (EXAMPLE.len() * 3 + 13) as u64);
+    }
+
+    #[test]
+    fn test_validate_stream_invalid_utf8() {
+        let input = b"1-3 a: abcde\n1-3 a: a\xffa\n2-9 c: ccccccccc\n";
+        let summary = validate_stream(&input[..], StreamOptions::default(), |_| {}).unwrap();
+
+        assert_eq!(
+            summary,
+            StreamSummary {
+                lines: 3,
+                valid: 2,
+                invalid: 1,
+                first_invalid: Some(2),
+            }
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use std::thread;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use day_3::generator;
+use day_3::index::TreeIndex;
+use day_3::traversal::Traversal;
+
+fn slope_sweep(c: &mut Criterion) {
+    let map = generator::generate_forest(0x2020, 1_000, 10_000, 0.25);
+    let slopes: Vec<(usize, usize)> = (1..=20)
+        .flat_map(|down| (0..200).map(move |right| (right, down)))
+        .collect();
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+
+    let mut group = c.benchmark_group("slope_sweep");
+    group.sample_size(10);
+
+    group.bench_function("traversal", |b| {
+        b.iter(|| {
+            slopes
+                .iter()
+                .map(|&(right, down)| {
+                    Traversal::new((right as isize, down as isize)).count_trees(black_box(&map))
+                })
+                .collect::<Vec<u64>>()
+        })
+    });
+
+    let index = TreeIndex::new(&map);
+
+    group.bench_function("index", |b| {
+        b.iter(|| {
+            slopes
+                .iter()
+                .map(|&slope| black_box(&index).count_trees(slope))
+                .collect::<Vec<u64>>()
+        })
+    });
+
+    group.bench_function("index_batched", |b| {
+        b.iter(|| black_box(&index).count_many(&slopes))
+    });
+
+    group.bench_function("index_parallel", |b| {
+        b.iter(|| black_box(&index).count_many_parallel(&slopes, threads))
+    });
+
+    group.finish();
+}
+
+fn huge_map(c: &mut Criterion) {
+    let (width, height) = (1_000, 100_000);
+
+    let mut group = c.benchmark_group("huge_map");
+    group.sample_size(10);
+
+    group.bench_function("generate", |b| {
+        b.iter(|| generator::generate_forest(black_box(7), width, height, 0.25))
+    });
+
+    let map = generator::generate_forest_on_slope(7, width, height, 0.25, (3, 1), 25_000).unwrap();
+
+    group.bench_function("traversal", |b| {
+        b.iter(|| Traversal::new((3, 1)).count_trees(black_box(&map)))
+    });
+
+    group.bench_function("index_build", |b| {
+        b.iter(|| TreeIndex::new(black_box(&map)))
+    });
+
+    let index = TreeIndex::new(&map);
+
+    group.bench_function("index", |b| {
+        b.iter(|| black_box(&index).count_trees((3, 1)))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, slope_sweep, huge_map);
+criterion_main!(benches);
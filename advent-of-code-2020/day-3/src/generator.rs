@@ -0,0 +1,197 @@
+//! Random forest maps of any size
+//!
+//! Maps are made only of open squares and trees. A map can also be planted with
+//! an exact number of trees along one slope, so the expected count for that
+//! slope is known without walking it.
+
+use std::fmt;
+
+use common::grid::Grid;
+use rand::rngs::StdRng;
+use rand::seq::index;
+use rand::{Rng, SeedableRng};
+
+use crate::traversal::Traversal;
+use crate::Map;
+
+/// More trees were requested along a slope than it has squares
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotEnoughSquares {
+    pub slope: (usize, usize),
+    pub requested: usize,
+    /// The number of squares the slope lands on
+    pub available: usize,
+}
+
+impl fmt::Display for NotEnoughSquares {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "cannot plant {} trees along slope {:?}, which only lands on {} squares",
+            self.requested, self.slope, self.available
+        )
+    }
+}
+
+impl std::error::Error for NotEnoughSquares {}
+
+/// Generates a map where each square is a tree with probability `density`
+///
+/// Panics if `density` is not between 0 and 1
+pub fn generate_map<R: Rng>(rng: &mut R, width: usize, height: usize, density: f64) -> Grid<Map> {
+    let cells = (0..width * height)
+        .map(|_| {
+            if rng.gen_bool(density) {
+                Map::Tree
+            } else {
+                Map::Open
+            }
+        })
+        .collect();
+
+    Grid::new(width, height, cells).unwrap()
+}
+
+/// Replaces every square the slope lands on so exactly `trees` of them are trees
+pub fn plant_trees_on_slope<R: Rng>(
+    rng: &mut R,
+    map: &mut Grid<Map>,
+    slope: (usize, usize),
+    trees: usize,
+) -> Result<(), NotEnoughSquares> {
+    let path: Vec<(usize, usize)> = Traversal::new((slope.0 as isize, slope.1 as isize))
+        .path(map)
+        .collect();
+
+    if trees > path.len() {
+        return Err(NotEnoughSquares {
+            slope,
+            requested: trees,
+            available: path.len(),
+        });
+    }
+
+    for &position in &path {
+        map[position] = Map::Open;
+    }
+    for chosen in index::sample(rng, path.len(), trees) {
+        map[path[chosen]] = Map::Tree;
+    }
+
+    Ok(())
+}
+
+/// Generates a map from a seed with the given size and tree density
+pub fn generate_forest(seed: u64, width: usize, height: usize, density: f64) -> Grid<Map> {
+    generate_map(&mut StdRng::seed_from_u64(seed), width, height, density)
+}
+
+/// Generates a map from a seed where the slope hits exactly `trees` trees
+pub fn generate_forest_on_slope(
+    seed: u64,
+    width: usize,
+    height: usize,
+    density: f64,
+    slope: (usize, usize),
+    trees: usize,
+) -> Result<Grid<Map>, NotEnoughSquares> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut map = generate_map(&mut rng, width, height, density);
+
+    plant_trees_on_slope(&mut rng, &mut map, slope, trees)?;
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::count_trees_on_slope;
+    use crate::index::TreeIndex;
+
+    /// Counts trees with plain arithmetic, for slopes that move down
+    fn reference_count(map: &Grid<Map>, (right, down): (usize, usize)) -> u64 {
+        (1..)
+            .map(|step| (step * right % map.width(), step * down))
+            .take_while(|&(_, row)| row < map.height())
+            .filter(|&position| map[position] == Map::Tree)
+            .count() as u64
+    }
+
+    #[test]
+    fn test_count_trees_matches_reference() {
+        let mut rng = StdRng::seed_from_u64(3);
+
+        for seed in 0..200 {
+            let width = rng.gen_range(1..=80);
+            let height = rng.gen_range(1..=60);
+            let density = rng.gen_range(0.0..=1.0);
+            let map = generate_forest(seed, width, height, density);
+            let index = TreeIndex::new(&map);
+
+            for _ in 0..20 {
+                let slope = (rng.gen_range(0..=2 * width), rng.gen_range(1..=height + 1));
+                let expected = reference_count(&map, slope);
+
+                assert_eq!(count_trees_on_slope(&map, &slope), expected, "{:?}", slope);
+                assert_eq!(index.count_trees(slope), expected, "{:?}", slope);
+            }
+        }
+    }
+
+    #[test]
+    fn test_planted_trees_are_counted() {
+        let mut rng = StdRng::seed_from_u64(11);
+
+        for seed in 0..200 {
+            let width = rng.gen_range(1..=50);
+            let height = rng.gen_range(2..=50);
+            let slope = (rng.gen_range(0..=width), rng.gen_range(1..height));
+            let squares = (height - 1) / slope.1;
+            let trees = rng.gen_range(0..=squares);
+            let density = rng.gen_range(0.0..=1.0);
+
+            let map = generate_forest_on_slope(seed, width, height, density, slope, trees).unwrap();
+
+            assert_eq!(count_trees_on_slope(&map, &slope), trees as u64);
+        }
+    }
+
+    #[test]
+    fn test_too_many_trees() {
+        assert_eq!(
+            generate_forest_on_slope(1, 10, 11, 0.5, (3, 2), 6),
+            Err(NotEnoughSquares {
+                slope: (3, 2),
+                requested: 6,
+                available: 5,
+            })
+        );
+    }
+
+    #[test]
+    fn test_density() {
+        let empty = generate_forest(5, 30, 30, 0.0);
+        let full = generate_forest(5, 30, 30, 1.0);
+
+        assert!(empty.iter().all(|(_, &cell)| cell == Map::Open));
+        assert!(full.iter().all(|(_, &cell)| cell == Map::Tree));
+
+        let trees = generate_forest(5, 100, 100, 0.25)
+            .iter()
+            .filter(|(_, &cell)| cell == Map::Tree)
+            .count();
+        assert!((2_000..3_000).contains(&trees), "{}", trees);
+    }
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        assert_eq!(
+            generate_forest(42, 40, 40, 0.3),
+            generate_forest(42, 40, 40, 0.3)
+        );
+        assert_ne!(
+            generate_forest(42, 40, 40, 0.3),
+            generate_forest(43, 40, 40, 0.3)
+        );
+    }
+}
@@ -0,0 +1,273 @@
+//! A packed index of tree positions for answering many slope queries
+//!
+//! Each row of the map is stored as a bitset with one bit per square, so a
+//! whole map fits in a fraction of the memory of a `Grid<Map>` and a lookup is
+//! a shift and a mask. Counts match `count_trees_on_slope` for every slope on
+//! maps made only of open squares and trees; any other terrain is treated as
+//! open, so check `TreeIndex::supports` before indexing a map that may have it.
+
+use std::collections::BTreeMap;
+use std::thread;
+
+use common::grid::Grid;
+
+use crate::Map;
+
+const WORD_BITS: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeIndex {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    bits: Vec<u64>,
+}
+
+impl TreeIndex {
+    /// Packs the trees on the map into one bitset per row
+    pub fn new(map: &Grid<Map>) -> Self {
+        let words_per_row = map.width().div_ceil(WORD_BITS);
+        let mut bits = vec![0; words_per_row * map.height()];
+
+        for ((column, row), cell) in map.iter() {
+            if *cell == Map::Tree {
+                bits[row * words_per_row + column / WORD_BITS] |= 1 << (column % WORD_BITS);
+            }
+        }
+
+        TreeIndex {
+            width: map.width(),
+            height: map.height(),
+            words_per_row,
+            bits,
+        }
+    }
+
+    /// Determines if the index gives the same counts as a traversal of the map,
+    /// which is when every square is open or a tree
+    pub fn supports(map: &Grid<Map>) -> bool {
+        map.iter()
+            .all(|(_, cell)| matches!(cell, Map::Open | Map::Tree))
+    }
+
+    /// Determines if there is a tree at the position, which must be on the map
+    pub fn is_tree(&self, column: usize, row: usize) -> bool {
+        let word = self.bits[row * self.words_per_row + column / WORD_BITS];
+        word >> (column % WORD_BITS) & 1 == 1
+    }
+
+    /// Gets the packed bits of a single row
+    fn row_words(&self, row: usize) -> &[u64] {
+        &self.bits[row * self.words_per_row..(row + 1) * self.words_per_row]
+    }
+
+    /// Counts the trees hit on a single slope
+    pub fn count_trees(&self, slope: (usize, usize)) -> u64 {
+        if self.width == 0 {
+            return 0;
+        }
+
+        let (right, down) = (slope.0 % self.width, slope.1);
+
+        // Without moving down the path circles along the first row back to the start
+        if down == 0 {
+            let mut column = right;
+            let mut trees = 0;
+
+            while column != 0 {
+                trees += self.is_tree(column, 0) as u64;
+                column = (column + right) % self.width;
+            }
+
+            return trees;
+        }
+
+        let mut column = 0;
+        let mut trees = 0;
+
+        for row in (down..self.height).step_by(down) {
+            column += right;
+            if column >= self.width {
+                column -= self.width;
+            }
+
+            trees += self.is_tree(column, row) as u64;
+        }
+
+        trees
+    }
+
+    /// Counts the trees hit on every slope, in the same order as `slopes`
+    ///
+    /// Slopes that move down by the same amount visit the same rows, so they
+    /// are walked together to read each row only once per group.
+    pub fn count_many(&self, slopes: &[(usize, usize)]) -> Vec<u64> {
+        let mut counts = vec![0; slopes.len()];
+
+        if self.width == 0 {
+            return counts;
+        }
+
+        let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (index, &(_, down)) in slopes.iter().enumerate() {
+            groups.entry(down).or_default().push(index);
+        }
+
+        for (down, indices) in groups {
+            if down == 0 {
+                for index in indices {
+                    counts[index] = self.count_trees(slopes[index]);
+                }
+                continue;
+            }
+
+            let steps: Vec<usize> = indices
+                .iter()
+                .map(|&index| slopes[index].0 % self.width)
+                .collect();
+            let mut columns = vec![0; indices.len()];
+            let mut trees = vec![0; indices.len()];
+
+            for row in (down..self.height).step_by(down) {
+                let words = self.row_words(row);
+
+                for ((column, step), trees) in columns.iter_mut().zip(&steps).zip(&mut trees) {
+                    *column += step;
+                    if *column >= self.width {
+                        *column -= self.width;
+                    }
+
+                    *trees += words[*column / WORD_BITS] >> (*column % WORD_BITS) & 1;
+                }
+            }
+
+            for (index, trees) in indices.into_iter().zip(trees) {
+                counts[index] = trees;
+            }
+        }
+
+        counts
+    }
+
+    /// Counts the trees hit on every slope, split across `threads` threads
+    pub fn count_many_parallel(&self, slopes: &[(usize, usize)], threads: usize) -> Vec<u64> {
+        let chunk_size = slopes.len().div_ceil(threads.max(1));
+
+        if chunk_size == 0 {
+            return Vec::new();
+        }
+
+        thread::scope(|scope| {
+            let handles: Vec<_> = slopes
+                .chunks(chunk_size)
+                .map(|chunk| scope.spawn(move || self.count_many(chunk)))
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traversal::Traversal;
+    use crate::{parse_map, ParseMode};
+
+    /// Builds a map wider than one word from a fixed pseudo-random sequence
+    fn wide_map() -> Grid<Map> {
+        let mut state: u32 = 3;
+        let input: String = (0..40)
+            .map(|_| {
+                let row: String = (0..70)
+                    .map(|_| {
+                        state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                        if state >> 16 & 3 == 0 {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect();
+                row + "\n"
+            })
+            .collect();
+
+        parse_map(&input, ParseMode::Strict).unwrap()
+    }
+
+    fn all_slopes() -> Vec<(usize, usize)> {
+        (0..=6)
+            .flat_map(|down| (0..=150).map(move |right| (right, down)))
+            .collect()
+    }
+
+    fn naive_counts(map: &Grid<Map>, slopes: &[(usize, usize)]) -> Vec<u64> {
+        slopes
+            .iter()
+            .map(|&(right, down)| Traversal::new((right as isize, down as isize)).count_trees(map))
+            .collect()
+    }
+
+    #[test]
+    fn test_is_tree() {
+        let map = wide_map();
+        let index = TreeIndex::new(&map);
+
+        for ((column, row), cell) in map.iter() {
+            assert_eq!(index.is_tree(column, row), *cell == Map::Tree);
+        }
+    }
+
+    #[test]
+    fn test_count_trees_matches_traversal() {
+        let map = wide_map();
+        let index = TreeIndex::new(&map);
+        let slopes = all_slopes();
+        let counts: Vec<u64> = slopes
+            .iter()
+            .map(|&slope| index.count_trees(slope))
+            .collect();
+
+        assert_eq!(counts, naive_counts(&map, &slopes));
+    }
+
+    #[test]
+    fn test_count_many_matches_traversal() {
+        let map = wide_map();
+        let index = TreeIndex::new(&map);
+        let mut slopes = all_slopes();
+        slopes.reverse();
+
+        assert_eq!(index.count_many(&slopes), naive_counts(&map, &slopes));
+    }
+
+    #[test]
+    fn test_supports() {
+        let mut map = wide_map();
+        assert!(TreeIndex::supports(&map));
+
+        *map.get_mut(69, 39).unwrap() = Map::Snow;
+        assert!(!TreeIndex::supports(&map));
+    }
+
+    #[test]
+    fn test_count_many_parallel() {
+        let map = wide_map();
+        let index = TreeIndex::new(&map);
+        let slopes = all_slopes();
+
+        assert_eq!(
+            index.count_many_parallel(&slopes, 4),
+            index.count_many(&slopes)
+        );
+        assert_eq!(
+            index.count_many_parallel(&slopes[..3], 8),
+            index.count_many(&slopes[..3])
+        );
+        assert!(index.count_many_parallel(&[], 4).is_empty());
+    }
+}
@@ -0,0 +1,276 @@
+//! Maps the characters of a map file to terrain and sets how terrain behaves
+//!
+//! A legend file has one entry per line: a single character, whitespace, and
+//! the name of a terrain (`open`, `tree`, `rock`, `ice` or `snow`). The name may
+//! be followed by rules that replace the terrain's built-in ones: `cost=N`,
+//! `slide=N`, and `stop` or `stop=false`. Blank lines and lines starting with
+//! `//` are ignored. For example:
+//!
+//! ```text
+//! // The puzzle's squares plus frozen ponds that slide twice as far
+//! . open
+//! # tree
+//! ~ ice slide=2
+//! ```
+//!
+//! The rules are used by `Traversal::traverse_with` and `Traversal::visits_with`.
+//! Tree counts, slope searches, rendering and route finding keep the built-in
+//! rules, so the puzzle answers do not depend on the legend.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{Map, TerrainRules};
+
+/// Describes why a legend could not be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LegendError {
+    /// A line was not a single character followed by a terrain name
+    InvalidEntry {
+        line: usize,
+    },
+    UnknownTerrain {
+        line: usize,
+        name: String,
+    },
+    DuplicateSymbol {
+        line: usize,
+        symbol: char,
+    },
+    /// A rule was not `cost=N`, `slide=N`, `stop` or `stop=false`
+    InvalidRule {
+        line: usize,
+        rule: String,
+    },
+    /// The terrain was given different rules on an earlier line
+    ConflictingRules {
+        line: usize,
+        name: String,
+    },
+}
+
+impl fmt::Display for LegendError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LegendError::InvalidEntry { line } => write!(
+                f,
+                "line {} should be a character followed by a terrain name",
+                line
+            ),
+            LegendError::UnknownTerrain { line, name } => {
+                write!(f, "unknown terrain '{}' on line {}", name, line)
+            }
+            LegendError::DuplicateSymbol { line, symbol } => {
+                write!(f, "'{}' is defined again on line {}", symbol, line)
+            }
+            LegendError::InvalidRule { line, rule } => {
+                write!(f, "invalid rule '{}' on line {}", rule, line)
+            }
+            LegendError::ConflictingRules { line, name } => write!(
+                f,
+                "'{}' is given different rules again on line {}",
+                name, line
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LegendError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Legend {
+    symbols: HashMap<char, Map>,
+    /// Rules that replace the built-in ones, by terrain
+    rules: HashMap<Map, TerrainRules>,
+}
+
+impl Default for Legend {
+    /// The puzzle's legend: `.` is open and `#` is a tree
+    fn default() -> Self {
+        let symbols = [('.', Map::Open), ('#', Map::Tree)]
+            .iter()
+            .copied()
+            .collect();
+
+        Legend {
+            symbols,
+            rules: HashMap::new(),
+        }
+    }
+}
+
+/// Applies a single `name=value` rule from a legend entry
+fn apply_rule(rules: &mut TerrainRules, rule: &str) -> Option<()> {
+    match rule.split_once('=') {
+        None if rule == "stop" => rules.stops = true,
+        Some(("stop", value)) => rules.stops = value.parse().ok()?,
+        Some(("cost", value)) => rules.cost = value.parse().ok()?,
+        Some(("slide", value)) => rules.slide = value.parse().ok()?,
+        _ => return None,
+    }
+
+    Some(())
+}
+
+impl Legend {
+    /// Parses the text of a legend file
+    pub fn parse(input: &str) -> Result<Self, LegendError> {
+        let mut symbols = HashMap::new();
+        let mut rules: HashMap<Map, TerrainRules> = HashMap::new();
+
+        for (index, line) in input.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+
+            let mut chars = line.chars();
+            let symbol = chars.next().unwrap();
+            let name = chars.as_str();
+
+            if !name.starts_with(char::is_whitespace) {
+                return Err(LegendError::InvalidEntry { line: line_number });
+            }
+
+            let mut words = name.split_whitespace();
+            let name = words.next().unwrap();
+            let terrain = Map::from_name(name).ok_or_else(|| LegendError::UnknownTerrain {
+                line: line_number,
+                name: name.to_owned(),
+            })?;
+
+            let mut terrain_rules = terrain.rules();
+            let mut has_rules = false;
+            for rule in words {
+                apply_rule(&mut terrain_rules, rule).ok_or_else(|| LegendError::InvalidRule {
+                    line: line_number,
+                    rule: rule.to_owned(),
+                })?;
+                has_rules = true;
+            }
+
+            if has_rules && *rules.entry(terrain).or_insert(terrain_rules) != terrain_rules {
+                return Err(LegendError::ConflictingRules {
+                    line: line_number,
+                    name: name.to_owned(),
+                });
+            }
+
+            if symbols.insert(symbol, terrain).is_some() {
+                return Err(LegendError::DuplicateSymbol {
+                    line: line_number,
+                    symbol,
+                });
+            }
+        }
+
+        Ok(Legend { symbols, rules })
+    }
+
+    /// Gets the terrain for a character if the legend has one
+    pub fn get(&self, symbol: char) -> Option<Map> {
+        self.symbols.get(&symbol).copied()
+    }
+
+    /// Gets the rules for a terrain, which are the built-in ones unless the
+    /// legend replaced them
+    pub fn rules(&self, terrain: Map) -> TerrainRules {
+        self.rules
+            .get(&terrain)
+            .copied()
+            .unwrap_or_else(|| terrain.rules())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_legend() {
+        let legend = Legend::default();
+
+        assert_eq!(legend.get('.'), Some(Map::Open));
+        assert_eq!(legend.get('#'), Some(Map::Tree));
+        assert_eq!(legend.get('~'), None);
+    }
+
+    #[test]
+    fn test_parse_legend() {
+        let legend =
+            Legend::parse("// comment\n. open\n# tree\n\n  R   rock\n~ ice\n* snow\n").unwrap();
+
+        assert_eq!(legend.get('R'), Some(Map::Rock));
+        assert_eq!(legend.get('~'), Some(Map::Ice));
+        assert_eq!(legend.get('*'), Some(Map::Snow));
+        assert_eq!(legend.get('.'), Some(Map::Open));
+        assert_eq!(legend.rules(Map::Snow), Map::Snow.rules());
+    }
+
+    #[test]
+    fn test_parse_legend_rules() {
+        let legend =
+            Legend::parse(". open\n# tree cost=3 stop\n~ ice slide=2\nR rock stop=false\nr rock")
+                .unwrap();
+
+        assert_eq!(legend.get('r'), Some(Map::Rock));
+        assert_eq!(legend.rules(Map::Open), Map::Open.rules());
+        assert_eq!(
+            legend.rules(Map::Tree),
+            TerrainRules {
+                cost: 3,
+                stops: true,
+                slide: 0
+            }
+        );
+        assert_eq!(legend.rules(Map::Ice).slide, 2);
+        assert_eq!(legend.rules(Map::Ice).cost, Map::Ice.cost());
+        assert!(!legend.rules(Map::Rock).stops);
+    }
+
+    #[test]
+    fn test_parse_legend_errors() {
+        assert_eq!(
+            Legend::parse(". open\n#tree"),
+            Err(LegendError::InvalidEntry { line: 2 })
+        );
+        assert_eq!(
+            Legend::parse("x lava"),
+            Err(LegendError::UnknownTerrain {
+                line: 1,
+                name: "lava".to_owned()
+            })
+        );
+        assert_eq!(
+            Legend::parse(". open\n. snow"),
+            Err(LegendError::DuplicateSymbol {
+                line: 2,
+                symbol: '.'
+            })
+        );
+        assert_eq!(
+            Legend::parse("~ ice slide=-1"),
+            Err(LegendError::InvalidRule {
+                line: 1,
+                rule: "slide=-1".to_owned()
+            })
+        );
+        assert_eq!(
+            Legend::parse("* snow weight=2"),
+            Err(LegendError::InvalidRule {
+                line: 1,
+                rule: "weight=2".to_owned()
+            })
+        );
+        assert_eq!(
+            Legend::parse("~ ice slide=2\n- ice slide=3"),
+            Err(LegendError::ConflictingRules {
+                line: 2,
+                name: "ice".to_owned()
+            })
+        );
+        assert!(Legend::parse("~ ice slide=2\n- ice slide=2\n_ ice").is_ok());
+    }
+}
@@ -0,0 +1,340 @@
+use std::fmt;
+use std::fs;
+
+use common::grid::{Grid, GridError};
+
+pub mod generator;
+pub mod index;
+pub mod legend;
+pub mod pathfinding;
+pub mod render;
+pub mod search;
+pub mod traversal;
+
+use legend::Legend;
+use traversal::Traversal;
+
+/// Controls how malformed maps are handled while parsing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Rejects characters missing from the legend and rows of different widths
+    Strict,
+    /// Accepts any map the way the original parser did
+    ///
+    /// Surrounding whitespace is trimmed from each row, blank rows are skipped and
+    /// any character missing from the legend is treated as a tree. Rows are cut or
+    /// padded with open squares to the width of the first row, since that is the
+    /// width the pattern repeats at.
+    Lenient,
+}
+
+/// Parse input into a grid of `Map` objects
+pub fn parse_input(filename: &str, mode: ParseMode, legend: &Legend) -> Grid<Map> {
+    let input = match fs::read_to_string(filename) {
+        Ok(input) => input,
+        Err(e) => panic!("Error reading file: {}", e),
+    };
+
+    match parse_map_with_legend(&input, mode, legend) {
+        Ok(grid) => grid,
+        Err(e) => panic!("Error parsing map: {}", e),
+    }
+}
+
+/// Parses the text of a map, one row per line, with the puzzle's `.` and `#` squares
+pub fn parse_map(input: &str, mode: ParseMode) -> Result<Grid<Map>, GridError> {
+    parse_map_with_legend(input, mode, &Legend::default())
+}
+
+/// Parses the text of a map, converting characters to terrain with the legend
+pub fn parse_map_with_legend(
+    input: &str,
+    mode: ParseMode,
+    legend: &Legend,
+) -> Result<Grid<Map>, GridError> {
+    let input = input.trim_end_matches(&['\r', '\n'][..]);
+
+    match mode {
+        ParseMode::Strict => Grid::try_from_str(input, |c| legend.get(c)),
+        ParseMode::Lenient => {
+            let mut rows: Vec<Vec<Map>> = input
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(|line| {
+                    line.chars()
+                        .map(|c| legend.get(c).unwrap_or(Map::Tree))
+                        .collect()
+                })
+                .collect();
+
+            let width = rows.first().map_or(0, Vec::len);
+            for row in rows.iter_mut() {
+                row.resize(width, Map::Open);
+            }
+
+            Grid::from_rows(rows)
+        }
+    }
+}
+
+/// The terrain of a single square of the map
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Map {
+    Open,
+    Tree,
+    /// Stops the toboggan dead
+    Rock,
+    /// Carries the toboggan an extra step along its slope without touching down
+    Ice,
+    /// Costs twice as much to cross as any other square
+    Snow,
+}
+
+impl Map {
+    /// Every kind of terrain, in the order they are reported
+    pub const ALL: [Map; 5] = [Map::Open, Map::Tree, Map::Rock, Map::Ice, Map::Snow];
+
+    /// Looks up terrain by the name used in legend files
+    pub fn from_name(name: &str) -> Option<Map> {
+        Map::ALL
+            .iter()
+            .copied()
+            .find(|terrain| terrain.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Map::Open => "open",
+            Map::Tree => "tree",
+            Map::Rock => "rock",
+            Map::Ice => "ice",
+            Map::Snow => "snow",
+        }
+    }
+
+    /// The character used when drawing this terrain
+    pub fn symbol(self) -> char {
+        match self {
+            Map::Open => '.',
+            Map::Tree => '#',
+            Map::Rock => '@',
+            Map::Ice => '~',
+            Map::Snow => '*',
+        }
+    }
+
+    /// The cost of landing on this terrain
+    pub fn cost(self) -> u64 {
+        match self {
+            Map::Snow => 2,
+            _ => 1,
+        }
+    }
+
+    /// Whether landing on this terrain ends a traversal
+    pub fn stops(self) -> bool {
+        self == Map::Rock
+    }
+
+    /// The number of extra slope steps taken after landing on this terrain
+    pub fn slide(self) -> usize {
+        match self {
+            Map::Ice => 1,
+            _ => 0,
+        }
+    }
+
+    /// The built-in rules for this terrain, which a legend may replace
+    pub fn rules(self) -> TerrainRules {
+        TerrainRules {
+            cost: self.cost(),
+            stops: self.stops(),
+            slide: self.slide(),
+        }
+    }
+}
+
+/// How landing on a kind of terrain affects a traversal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TerrainRules {
+    /// The cost of landing on the terrain
+    pub cost: u64,
+    /// Whether landing on the terrain ends the traversal
+    pub stops: bool,
+    /// The number of extra slope steps taken after landing on the terrain
+    pub slide: usize,
+}
+
+/// The product of tree counts grew too large to represent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProductOverflow {
+    /// The slope whose tree count caused the overflow
+    pub slope: (usize, usize),
+    /// The product of the tree counts before that slope
+    pub product: u128,
+    pub trees: u64,
+}
+
+impl fmt::Display for ProductOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "product of tree counts overflowed at slope {:?}: {} * {}",
+            self.slope, self.product, self.trees
+        )
+    }
+}
+
+impl std::error::Error for ProductOverflow {}
+
+/// Counts the number of trees along a path given a slope
+///
+/// See `Traversal` for paths with other directions, starting points or edges
+fn count_trees_on_slope(input: &Grid<Map>, slope: &(usize, usize)) -> u64 {
+    Traversal::new((slope.0 as isize, slope.1 as isize)).count_trees(input)
+}
+
+/// Counts the tree along a single path given the slope of a the path
+pub fn puzzle_one_solution(input: &Grid<Map>, slope: (usize, usize)) -> u64 {
+    count_trees_on_slope(input, &slope)
+}
+
+/// Evaluates the product of tree counts for multiple slopes
+///
+/// Returns an error naming the slope at which the product no longer fits in a `u128`
+pub fn puzzle_two_solution(
+    input: &Grid<Map>,
+    slopes: &[(usize, usize)],
+) -> Result<u128, ProductOverflow> {
+    slopes.iter().try_fold(1u128, |acc, slope| {
+        let trees = count_trees_on_slope(input, slope);
+
+        acc.checked_mul(trees as u128).ok_or(ProductOverflow {
+            slope: *slope,
+            product: acc,
+            trees,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The example map from the puzzle, shared by the tests of every module
+    pub(crate) const EXAMPLE_MAP: &str = "..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#";
+
+    pub(crate) fn example_input() -> Grid<Map> {
+        parse_map(EXAMPLE_MAP, ParseMode::Strict).unwrap()
+    }
+
+    /// A small map for the traversal and rendering tests
+    pub(crate) fn small_map() -> Grid<Map> {
+        parse_map("#..\n.#.\n..#\n#..", ParseMode::Strict).unwrap()
+    }
+
+    #[test]
+    fn test_puzzle_one_example_input() {
+        let input = example_input();
+        let slope = (3, 1);
+
+        assert_eq!(puzzle_one_solution(&input, slope), 7);
+    }
+
+    #[test]
+    fn test_puzzle_one_different_slopes() {
+        let input = example_input();
+
+        assert_eq!(puzzle_one_solution(&input, (1, 1)), 2);
+        assert_eq!(puzzle_one_solution(&input, (5, 1)), 3);
+        assert_eq!(puzzle_one_solution(&input, (7, 1)), 4);
+        assert_eq!(puzzle_one_solution(&input, (1, 2)), 2);
+    }
+
+    #[test]
+    fn test_puzzle_two_example_input() {
+        let input = example_input();
+        let slopes = vec![
+            (1, 1),
+            (3, 1),
+            (5, 1),
+            (7, 1),
+            (1, 2),
+        ];
+
+        assert_eq!(puzzle_two_solution(&input, &slopes), Ok(336))
+    }
+
+    #[test]
+    fn test_strict_rejects_unknown_characters() {
+        assert_eq!(
+            parse_map("..#\n.O.\n", ParseMode::Strict),
+            Err(GridError::UnknownCell {
+                line: 2,
+                column: 2,
+                found: 'O'
+            })
+        );
+    }
+
+    #[test]
+    fn test_strict_rejects_ragged_rows() {
+        assert_eq!(
+            parse_map("..#\n.#\n", ParseMode::Strict),
+            Err(GridError::RaggedRow {
+                line: 2,
+                expected: 3,
+                found: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_lenient_repairs_map() {
+        let map = parse_map("..#\r\n .O \n#.#.#\n", ParseMode::Lenient).unwrap();
+        let expected = parse_map("..#\n.#.\n#.#", ParseMode::Strict).unwrap();
+
+        assert_eq!(map, expected);
+    }
+
+    #[test]
+    fn test_lenient_skips_blank_lines() {
+        let expected = parse_map("..#\n#..", ParseMode::Strict).unwrap();
+
+        let leading = parse_map("  \n..#\n#..", ParseMode::Lenient).unwrap();
+        assert_eq!(leading, expected);
+        assert_eq!(puzzle_one_solution(&leading, (3, 1)), 1);
+
+        let middle = parse_map("..#\n\n \t\n#..\n", ParseMode::Lenient).unwrap();
+        assert_eq!(middle, expected);
+    }
+
+    #[test]
+    fn test_puzzle_two_overflow() {
+        let input = parse_map(&"#\n".repeat(1001), ParseMode::Strict).unwrap();
+
+        assert_eq!(
+            puzzle_two_solution(&input, &[(0, 1); 12]),
+            Ok(10u128.pow(36))
+        );
+        assert_eq!(
+            puzzle_two_solution(&input, &[(0, 1); 13]),
+            Err(ProductOverflow {
+                slope: (0, 1),
+                product: 10u128.pow(36),
+                trees: 1000,
+            })
+        );
+    }
+}
@@ -1,139 +1,161 @@
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+use std::fmt::Debug;
+use std::fs;
+use std::str::FromStr;
+
+use day_3::generator;
+use day_3::legend::Legend;
+use day_3::pathfinding::{self, Algorithm, PathOptions};
+use day_3::traversal::Traversal;
+use day_3::{
+    parse_input, puzzle_one_solution, puzzle_two_solution, render, search, Map, ParseMode,
+};
+
+/// Finds the value following a flag such as `--svg path`
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .map(String::as_str)
+}
 
-/// Creates an iterator over the lines of the puzzle input
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
+/// Parses a slope, bound or map size written as two numbers such as `right,down`
+fn parse_slope<T>(value: &str) -> (T, T)
 where
-    P: AsRef<Path>,
+    T: FromStr,
+    T::Err: Debug,
 {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    match value.split_once(',') {
+        Some((right, down)) => (right.parse().unwrap(), down.parse().unwrap()),
+        None => panic!("Slopes are written as right,down, found '{}'", value),
+    }
 }
 
-/// Parse input into a vector of vector of `Map` objects
-fn parse_input(filename: &str) -> Vec<Vec<Map>> {
-    let mut results = Vec::new();
-
-    match read_lines(filename) {
-        Ok(lines) => {
-            for line in lines {
-                let line = line.unwrap();
-
-                let parsed = line
-                    .trim()
-                    .chars()
-                    .map(|c| if c == '.' { Map::Open } else { Map::Tree })
-                    .collect();
-
-                results.push(parsed);
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if let Some(size) = flag_value(&args, "--generate") {
+        let (width, height) = parse_slope(size);
+        let density = flag_value(&args, "--density").map_or(0.25, |d| d.parse().unwrap());
+        let seed = flag_value(&args, "--seed").map_or(2020, |s| s.parse().unwrap());
+        let map = match flag_value(&args, "--trees") {
+            Some(trees) => {
+                let slope = flag_value(&args, "--slope").map_or((3, 1), parse_slope);
+                generator::generate_forest_on_slope(
+                    seed,
+                    width,
+                    height,
+                    density,
+                    slope,
+                    trees.parse().unwrap(),
+                )
+                .unwrap_or_else(|e| panic!("Error generating map: {}", e))
             }
+            None => generator::generate_forest(seed, width, height, density),
+        };
+
+        for row in map.rows() {
+            println!(
+                "{}",
+                row.iter().map(|cell| cell.symbol()).collect::<String>()
+            );
         }
-        Err(e) => panic!("Error reading file: {}", e),
-    }
 
-    results
-}
+        return;
+    }
 
-pub enum Map {
-    Open,
-    Tree,
-}
+    let mode = if args.iter().any(|arg| arg == "--lenient") {
+        ParseMode::Lenient
+    } else {
+        ParseMode::Strict
+    };
+    let legend = match flag_value(&args, "--legend") {
+        Some(filename) => {
+            let legend = fs::read_to_string(filename)
+                .unwrap_or_else(|e| panic!("Error reading file: {}", e));
+            Legend::parse(&legend).unwrap_or_else(|e| panic!("Error parsing legend: {}", e))
+        }
+        None => Legend::default(),
+    };
+    let input = parse_input("./puzzle_input.txt", mode, &legend);
 
-/// Counts the number of trees along a path given a slope
-fn count_trees_on_slope(input: &[Vec<Map>], slope: &(usize, usize)) -> u32 {
-    let mut tree_count = 0;
-    let mut row = slope.1;
-    let mut column = slope.0;
+    if args.iter().any(|arg| arg == "--stats") {
+        let slope = flag_value(&args, "--slope").map_or((3, 1), parse_slope);
+        let stats = Traversal::new(slope).traverse_with(&input, &legend);
 
-    while row < input.len() {
-        if let Map::Tree = input[row][column] {
-            tree_count += 1;
+        for terrain in Map::ALL.iter() {
+            println!("{} -> {}", terrain.name(), stats.visits(*terrain));
+        }
+        println!("Cost -> {}", stats.cost);
+        if let Some(position) = stats.stopped_at {
+            println!("Stopped At -> {:?}", position);
         }
 
-        row += slope.1;
-        column = (column + slope.0) % input[0].len();
+        return;
     }
 
-    tree_count
-}
+    if args.iter().any(|arg| arg == "--render") || flag_value(&args, "--svg").is_some() {
+        let slope = flag_value(&args, "--slope").map_or((3, 1), parse_slope);
+        let traversal = Traversal::new(slope);
 
-/// Counts the tree along a single path given the slope of a the path
-pub fn puzzle_one_solution(input: &[Vec<Map>], slope: (usize, usize)) -> u32 {
-    count_trees_on_slope(input, &slope)
-}
-
-/// Evaluates the product of tree counts for multiple slopes
-pub fn puzzle_two_solution(input: &[Vec<Map>], slopes: &[(usize, usize)]) -> u32 {
-    slopes.iter()
-        .fold(1, |acc, slope| {
-            acc * count_trees_on_slope(input, slope)
-        })
-}
+        match flag_value(&args, "--svg") {
+            Some(filename) => fs::write(filename, render::render_svg(&input, &traversal))
+                .unwrap_or_else(|e| panic!("Error writing file: {}", e)),
+            None => {
+                let color = args.iter().any(|arg| arg == "--color");
+                print!("{}", render::render_text(&input, &traversal, color));
+            }
+        }
 
-fn main() {
-    let input = parse_input("./puzzle_input.txt");
+        return;
+    }
 
-    println!("Puzzle 1 Solution -> {}", puzzle_one_solution(&input, (3, 1)));
-    println!("Puzzle 2 Solution -> {}", puzzle_two_solution(&input, &[(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]));
-}
+    if let Some(algorithm) = flag_value(&args, "--route") {
+        let algorithm = match algorithm {
+            "bfs" => Algorithm::BreadthFirst,
+            "dijkstra" => Algorithm::Dijkstra,
+            "astar" => Algorithm::AStar,
+            other => panic!(
+                "Unknown algorithm '{}', expected bfs, dijkstra or astar",
+                other
+            ),
+        };
+        let options = PathOptions {
+            tree_cost: match flag_value(&args, "--tree-cost") {
+                Some("none") => None,
+                Some(cost) => Some(cost.parse().unwrap()),
+                None => PathOptions::default().tree_cost,
+            },
+            ..PathOptions::default()
+        };
+
+        match pathfinding::find_route(&input, &options, algorithm) {
+            Some(route) => {
+                println!("Route Cost -> {}", route.cost);
+                println!("Route Moves -> {}", route.squares.len() - 1);
+                println!("Route -> {:?}", route.squares);
+            }
+            None => println!("No route reaches the bottom of the map"),
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    fn example_input() -> Vec<Vec<Map>> {
-        let input = "..##.......
-        #...#...#..
-        .#....#..#.
-        ..#.#...#.#
-        .#...##..#.
-        ..#.##.....
-        .#.#.#....#
-        .#........#
-        #.##...#...
-        #...##....#
-        .#..#...#.#";
-
-        input
-            .split('\n')
-            .map(|line| {
-                let line = line.trim();
-                line.chars()
-                    .map(|c| if c == '.' { Map::Open } else { Map::Tree })
-                    .collect::<Vec<Map>>()
-            })
-            .collect::<Vec<Vec<Map>>>()
+        return;
     }
-    #[test]
-    fn test_puzzle_one_example_input() {
-        let input = example_input();
-        let slope = (3, 1);
 
-        assert_eq!(puzzle_one_solution(&input, slope), 7);
-    }
+    if let Some(bound) = flag_value(&args, "--search") {
+        let (max_right, max_down) = parse_slope(bound);
+        let results = search::search_slopes(&input, max_right, max_down);
 
-    #[test]
-    fn test_puzzle_one_different_slopes() {
-        let input = example_input();
+        for count in &results.counts {
+            println!("{:?} -> {}", count.slope, count.trees);
+        }
+        println!("Fewest Trees -> {:?}", results.fewest_trees());
+        println!("Most Trees -> {:?}", results.most_trees());
 
-        assert_eq!(puzzle_one_solution(&input, (1, 1)), 2);
-        assert_eq!(puzzle_one_solution(&input, (5, 1)), 3);
-        assert_eq!(puzzle_one_solution(&input, (7, 1)), 4);
-        assert_eq!(puzzle_one_solution(&input, (1, 2)), 2);
+        return;
     }
 
-    #[test]
-    fn test_puzzle_two_example_input() {
-        let input = example_input();
-        let slopes = vec![
-            (1, 1),
-            (3, 1),
-            (5, 1),
-            (7, 1),
-            (1, 2),
-        ];
-
-        assert_eq!(puzzle_two_solution(&input, &slopes), 336)
+    println!("Puzzle 1 Solution -> {}", puzzle_one_solution(&input, (3, 1)));
+    match puzzle_two_solution(&input, &[(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]) {
+        Ok(product) => println!("Puzzle 2 Solution -> {}", product),
+        Err(e) => panic!("Error solving puzzle 2: {}", e),
     }
 }
@@ -0,0 +1,357 @@
+//! Finds routes from the top of the map to the bottom
+//!
+//! Instead of a straight slope, the toboggan may take any sequence of the
+//! allowed moves. Landing on a square costs its terrain's `cost`, except trees
+//! which cost `tree_cost` and rocks which can never be entered. The starting
+//! square on the top row is free, but it must be one that could be entered.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use common::grid::Grid;
+
+use crate::Map;
+
+/// The search used to find a route
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    /// Finds the route with the fewest moves, ignoring costs
+    BreadthFirst,
+    /// Finds the cheapest route
+    Dijkstra,
+    /// Finds the cheapest route, guided by the number of rows left to descend
+    AStar,
+}
+
+/// Controls which routes are allowed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathOptions {
+    /// Offsets as `(right, down)` that can be taken from any square
+    pub moves: Vec<(isize, isize)>,
+    /// The cost of landing on a tree, or `None` if trees cannot be entered
+    pub tree_cost: Option<u64>,
+    /// Whether the map repeats to the left and right
+    pub wrap: bool,
+}
+
+impl Default for PathOptions {
+    /// Moves one square down, left or right on the puzzle's repeating map
+    fn default() -> Self {
+        PathOptions {
+            moves: vec![(0, 1), (-1, 0), (1, 0)],
+            tree_cost: Some(10),
+            wrap: true,
+        }
+    }
+}
+
+impl PathOptions {
+    /// The cost of landing on a square, or `None` if it cannot be entered
+    fn cost(&self, terrain: Map) -> Option<u64> {
+        match terrain {
+            Map::Tree => self.tree_cost,
+            Map::Rock => None,
+            other => Some(other.cost()),
+        }
+    }
+
+    /// The smallest amount any single move can cost
+    fn cheapest_move(&self) -> u64 {
+        Map::ALL
+            .iter()
+            .filter_map(|&terrain| self.cost(terrain))
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+/// A route from the top row to the bottom row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    /// Every square on the route as `(column, row)`, including the start
+    pub squares: Vec<(usize, usize)>,
+    pub cost: u64,
+}
+
+/// Finds a route from any square on the top row to any square on the bottom row
+///
+/// Returns `None` if the bottom row cannot be reached
+pub fn find_route(map: &Grid<Map>, options: &PathOptions, algorithm: Algorithm) -> Option<Route> {
+    if map.width() == 0 || map.height() == 0 {
+        return None;
+    }
+
+    match algorithm {
+        Algorithm::BreadthFirst => breadth_first(map, options),
+        Algorithm::Dijkstra => cheapest_first(map, options, false),
+        Algorithm::AStar => cheapest_first(map, options, true),
+    }
+}
+
+/// Lists the squares on the top row that a route may start from
+fn starts<'a>(
+    map: &'a Grid<Map>,
+    options: &'a PathOptions,
+) -> impl Iterator<Item = (usize, usize)> + 'a {
+    (0..map.width())
+        .map(|column| (column, 0))
+        .filter(move |&square| options.cost(map[square]).is_some())
+}
+
+/// Converts a position to an index into the flat list of squares
+fn index_of(map: &Grid<Map>, (column, row): (usize, usize)) -> usize {
+    row * map.width() + column
+}
+
+/// Lists the squares reachable in one move, with the cost of landing on each
+fn neighbors<'a>(
+    map: &'a Grid<Map>,
+    options: &'a PathOptions,
+    (column, row): (usize, usize),
+) -> impl Iterator<Item = ((usize, usize), u64)> + 'a {
+    options.moves.iter().filter_map(move |&(right, down)| {
+        let mut next_column = column as isize + right;
+        let next_row = row as isize + down;
+
+        if options.wrap {
+            next_column = next_column.rem_euclid(map.width() as isize);
+        }
+
+        let terrain = *map.get_bounded(next_column, next_row)?;
+        let cost = options.cost(terrain)?;
+
+        Some(((next_column as usize, next_row as usize), cost))
+    })
+}
+
+/// Follows the chain of previous squares back from the end of a route
+fn build_route(
+    map: &Grid<Map>,
+    previous: &[Option<(usize, usize)>],
+    end: (usize, usize),
+    cost: u64,
+) -> Route {
+    let mut squares = vec![end];
+    let mut current = end;
+
+    while let Some(square) = previous[index_of(map, current)] {
+        squares.push(square);
+        current = square;
+    }
+
+    squares.reverse();
+    Route { squares, cost }
+}
+
+fn breadth_first(map: &Grid<Map>, options: &PathOptions) -> Option<Route> {
+    let mut previous = vec![None; map.width() * map.height()];
+    let mut costs = vec![None; map.width() * map.height()];
+    let mut queue = VecDeque::new();
+
+    for start in starts(map, options) {
+        costs[index_of(map, start)] = Some(0);
+        queue.push_back(start);
+    }
+
+    while let Some(square) = queue.pop_front() {
+        let cost = costs[index_of(map, square)].unwrap();
+
+        if square.1 == map.height() - 1 {
+            return Some(build_route(map, &previous, square, cost));
+        }
+
+        for (next, step) in neighbors(map, options, square) {
+            let index = index_of(map, next);
+
+            if costs[index].is_none() {
+                costs[index] = Some(cost + step);
+                previous[index] = Some(square);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+fn cheapest_first(map: &Grid<Map>, options: &PathOptions, guided: bool) -> Option<Route> {
+    let mut previous = vec![None; map.width() * map.height()];
+    let mut costs = vec![u64::MAX; map.width() * map.height()];
+    let mut queue = BinaryHeap::new();
+
+    // Each move descends at most this many rows, so a route needs at least
+    // `rows left / deepest move` more moves, each costing at least `cheapest`
+    let deepest_move = options
+        .moves
+        .iter()
+        .map(|&(_, down)| down)
+        .max()
+        .unwrap_or(0);
+    let cheapest = options.cheapest_move();
+    let estimate = |row: usize| -> u64 {
+        if !guided || deepest_move <= 0 {
+            return 0;
+        }
+
+        let rows_left = (map.height() - 1 - row) as u64;
+        rows_left.div_ceil(deepest_move as u64) * cheapest
+    };
+
+    for start in starts(map, options) {
+        costs[index_of(map, start)] = 0;
+        queue.push(Reverse((estimate(0), 0, start)));
+    }
+
+    while let Some(Reverse((_, cost, square))) = queue.pop() {
+        if cost > costs[index_of(map, square)] {
+            continue;
+        }
+
+        if square.1 == map.height() - 1 {
+            return Some(build_route(map, &previous, square, cost));
+        }
+
+        for (next, step) in neighbors(map, options, square) {
+            let index = index_of(map, next);
+            let next_cost = cost + step;
+
+            if next_cost < costs[index] {
+                costs[index] = next_cost;
+                previous[index] = Some(square);
+                queue.push(Reverse((next_cost + estimate(next.1), next_cost, next)));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::legend::Legend;
+    use crate::tests::example_input;
+    use crate::{parse_map, parse_map_with_legend, ParseMode};
+
+    const ALGORITHMS: [Algorithm; 3] = [
+        Algorithm::BreadthFirst,
+        Algorithm::Dijkstra,
+        Algorithm::AStar,
+    ];
+
+    /// Checks that a route only takes allowed moves and adds up to its cost
+    fn assert_valid_route(map: &Grid<Map>, options: &PathOptions, route: &Route) {
+        assert_eq!(route.squares.first().unwrap().1, 0);
+        assert!(options.cost(map[route.squares[0]]).is_some());
+        assert_eq!(route.squares.last().unwrap().1, map.height() - 1);
+
+        let mut cost = 0;
+        for pair in route.squares.windows(2) {
+            let (_, step) = neighbors(map, options, pair[0])
+                .find(|&(square, _)| square == pair[1])
+                .expect("route takes a move that is not allowed");
+            cost += step;
+        }
+
+        assert_eq!(cost, route.cost);
+    }
+
+    #[test]
+    fn test_dijkstra_and_a_star_agree() {
+        let map = example_input();
+
+        for tree_cost in [0, 1, 2, 5, 100].iter() {
+            let options = PathOptions {
+                tree_cost: Some(*tree_cost),
+                ..PathOptions::default()
+            };
+
+            let dijkstra = find_route(&map, &options, Algorithm::Dijkstra).unwrap();
+            let a_star = find_route(&map, &options, Algorithm::AStar).unwrap();
+
+            assert_valid_route(&map, &options, &dijkstra);
+            assert_valid_route(&map, &options, &a_star);
+            assert_eq!(dijkstra.cost, a_star.cost);
+        }
+    }
+
+    #[test]
+    fn test_route_avoids_trees() {
+        let map = example_input();
+        let options = PathOptions {
+            tree_cost: None,
+            ..PathOptions::default()
+        };
+        let route = find_route(&map, &options, Algorithm::Dijkstra).unwrap();
+
+        assert_valid_route(&map, &options, &route);
+        assert!(route.squares.iter().all(|&square| map[square] != Map::Tree));
+    }
+
+    #[test]
+    fn test_breadth_first_takes_fewest_moves() {
+        let map = example_input();
+        let options = PathOptions::default();
+        let route = find_route(&map, &options, Algorithm::BreadthFirst).unwrap();
+
+        assert_valid_route(&map, &options, &route);
+        assert_eq!(route.squares.len(), map.height());
+    }
+
+    #[test]
+    fn test_cheapest_route_cost() {
+        let map = parse_map("..#\n#.#\n##.\n.#.", ParseMode::Strict).unwrap();
+        let options = PathOptions {
+            moves: vec![(0, 1), (1, 1)],
+            tree_cost: Some(10),
+            wrap: false,
+        };
+        let route = find_route(&map, &options, Algorithm::AStar).unwrap();
+
+        assert_eq!(route.squares, vec![(0, 0), (1, 1), (2, 2), (2, 3)]);
+        assert_eq!(route.cost, 3);
+    }
+
+    #[test]
+    fn test_unreachable_bottom_row() {
+        let legend = Legend::parse(". open\n# tree\nR rock").unwrap();
+        let map = parse_map_with_legend("...\nRRR\n...", ParseMode::Strict, &legend).unwrap();
+
+        for algorithm in ALGORITHMS.iter() {
+            assert_eq!(find_route(&map, &PathOptions::default(), *algorithm), None);
+        }
+
+        let upwards = PathOptions {
+            moves: vec![(0, -1)],
+            ..PathOptions::default()
+        };
+        assert_eq!(
+            find_route(&example_input(), &upwards, Algorithm::AStar),
+            None
+        );
+    }
+
+    #[test]
+    fn test_route_does_not_start_on_rock() {
+        let legend = Legend::parse(". open\n# tree\nR rock").unwrap();
+        let map = parse_map_with_legend("R.R\n..R\n...", ParseMode::Strict, &legend).unwrap();
+        let options = PathOptions {
+            wrap: false,
+            ..PathOptions::default()
+        };
+
+        for algorithm in ALGORITHMS.iter() {
+            let route = find_route(&map, &options, *algorithm).unwrap();
+
+            assert_valid_route(&map, &options, &route);
+            assert_eq!(route.squares, vec![(1, 0), (1, 1), (1, 2)]);
+        }
+
+        let blocked = parse_map_with_legend("RRR\n...", ParseMode::Strict, &legend).unwrap();
+        let single_row = parse_map_with_legend("R", ParseMode::Strict, &legend).unwrap();
+        for algorithm in ALGORITHMS.iter() {
+            assert_eq!(find_route(&blocked, &options, *algorithm), None);
+            assert_eq!(find_route(&single_row, &options, *algorithm), None);
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! Draws a traversal over the map
+//!
+//! Squares landed on are marked `O`, or `X` if a tree was hit, as in the puzzle
+//! text. Other squares are drawn with their terrain's symbol. Paths that wrap
+//! are folded back onto the single copy of the map, so a square is marked if
+//! any copy of it was visited.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use common::grid::Grid;
+
+use crate::traversal::Traversal;
+use crate::Map;
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_TREE: &str = "\x1b[32m";
+const ANSI_OTHER_TERRAIN: &str = "\x1b[37m";
+const ANSI_VISITED: &str = "\x1b[1;36m";
+const ANSI_TREE_HIT: &str = "\x1b[1;31m";
+
+/// The size of a single square in exported SVG images, in pixels
+const SVG_CELL_SIZE: usize = 10;
+
+/// How a single square of the rendered map is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Square {
+    Terrain(Map),
+    Visited,
+    TreeHit,
+}
+
+impl Square {
+    fn symbol(self) -> char {
+        match self {
+            Square::Terrain(terrain) => terrain.symbol(),
+            Square::Visited => 'O',
+            Square::TreeHit => 'X',
+        }
+    }
+
+    fn ansi_color(self) -> Option<&'static str> {
+        match self {
+            Square::Terrain(Map::Open) => None,
+            Square::Terrain(Map::Tree) => Some(ANSI_TREE),
+            Square::Terrain(_) => Some(ANSI_OTHER_TERRAIN),
+            Square::Visited => Some(ANSI_VISITED),
+            Square::TreeHit => Some(ANSI_TREE_HIT),
+        }
+    }
+
+    fn svg_color(self) -> &'static str {
+        match self {
+            Square::Terrain(Map::Open) => "#ffffff",
+            Square::Terrain(Map::Tree) => "#2e7d32",
+            Square::Terrain(Map::Rock) => "#757575",
+            Square::Terrain(Map::Ice) => "#b3e5fc",
+            Square::Terrain(Map::Snow) => "#eceff1",
+            Square::Visited => "#29b6f6",
+            Square::TreeHit => "#d32f2f",
+        }
+    }
+}
+
+/// Works out how every square should be drawn
+fn squares(map: &Grid<Map>, traversal: &Traversal) -> Grid<Square> {
+    let visited: HashSet<(usize, usize)> = traversal
+        .visits(map)
+        .map(|(position, _)| position)
+        .collect();
+    let cells = map
+        .iter()
+        .map(
+            |(position, &cell)| match (cell, visited.contains(&position)) {
+                (Map::Tree, true) => Square::TreeHit,
+                (_, true) => Square::Visited,
+                (terrain, false) => Square::Terrain(terrain),
+            },
+        )
+        .collect();
+
+    Grid::new(map.width(), map.height(), cells).unwrap()
+}
+
+/// Renders the map as text, optionally colored with ANSI escape codes
+pub fn render_text(map: &Grid<Map>, traversal: &Traversal, color: bool) -> String {
+    let mut output = String::new();
+
+    for row in squares(map, traversal).rows() {
+        for square in row {
+            match square.ansi_color() {
+                Some(code) if color => {
+                    write!(output, "{}{}{}", code, square.symbol(), ANSI_RESET).unwrap()
+                }
+                _ => output.push(square.symbol()),
+            }
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Renders the map as an SVG image with one square per cell
+pub fn render_svg(map: &Grid<Map>, traversal: &Traversal) -> String {
+    let mut output = String::new();
+
+    writeln!(
+        output,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}">"#,
+        map.width() * SVG_CELL_SIZE,
+        map.height() * SVG_CELL_SIZE
+    )
+    .unwrap();
+
+    for ((column, row), square) in squares(map, traversal).iter() {
+        writeln!(
+            output,
+            r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            column * SVG_CELL_SIZE,
+            row * SVG_CELL_SIZE,
+            SVG_CELL_SIZE,
+            SVG_CELL_SIZE,
+            square.svg_color()
+        )
+        .unwrap();
+    }
+
+    output.push_str("</svg>\n");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::small_map;
+
+    #[test]
+    fn test_render_text() {
+        let output = render_text(&small_map(), &Traversal::new((2, 1)), false);
+
+        assert_eq!(output, "#..\n.#O\n.O#\nX..\n");
+    }
+
+    #[test]
+    fn test_render_text_with_color() {
+        let output = render_text(&small_map(), &Traversal::new((2, 1)), true);
+        let last_line = output.lines().last().unwrap();
+
+        assert_eq!(last_line, "\x1b[1;31mX\x1b[0m..");
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let output = render_svg(&small_map(), &Traversal::new((2, 1)));
+
+        assert!(output
+            .starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="30" height="40">"#));
+        assert_eq!(output.matches("<rect").count(), 12);
+        assert!(output.contains(r##"<rect x="0" y="30" width="10" height="10" fill="#d32f2f"/>"##));
+    }
+}
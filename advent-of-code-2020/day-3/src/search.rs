@@ -0,0 +1,149 @@
+//! Searches for the best and worst slopes down the map
+
+use common::grid::Grid;
+
+use crate::index::TreeIndex;
+use crate::traversal::Traversal;
+use crate::Map;
+
+/// The number of trees hit on a single slope
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlopeCount {
+    pub slope: (usize, usize),
+    pub trees: u64,
+}
+
+/// The tree counts for every slope that was searched
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlopeSearch {
+    pub counts: Vec<SlopeCount>,
+}
+
+impl SlopeSearch {
+    /// Finds every slope that hits the fewest trees
+    pub fn fewest_trees(&self) -> Vec<(usize, usize)> {
+        let fewest = self.counts.iter().map(|count| count.trees).min();
+        self.slopes_with(fewest)
+    }
+
+    /// Finds every slope that hits the most trees
+    pub fn most_trees(&self) -> Vec<(usize, usize)> {
+        let most = self.counts.iter().map(|count| count.trees).max();
+        self.slopes_with(most)
+    }
+
+    fn slopes_with(&self, trees: Option<u64>) -> Vec<(usize, usize)> {
+        self.counts
+            .iter()
+            .filter(|count| Some(count.trees) == trees)
+            .map(|count| count.slope)
+            .collect()
+    }
+}
+
+/// Counts the trees on every slope with `right <= max_right` and `1 <= down <= max_down`
+///
+/// The table is ordered by `down` and then by `right`. Maps with terrain other
+/// than open squares and trees are traversed slope by slope instead of indexed.
+pub fn search_slopes(input: &Grid<Map>, max_right: usize, max_down: usize) -> SlopeSearch {
+    let slopes: Vec<(usize, usize)> = (1..=max_down)
+        .flat_map(|down| (0..=max_right).map(move |right| (right, down)))
+        .collect();
+    let trees = if TreeIndex::supports(input) {
+        TreeIndex::new(input).count_many(&slopes)
+    } else {
+        slopes
+            .iter()
+            .map(|&(right, down)| {
+                Traversal::new((right as isize, down as isize)).count_trees(input)
+            })
+            .collect()
+    };
+
+    let counts = slopes
+        .into_iter()
+        .zip(trees)
+        .map(|(slope, trees)| SlopeCount { slope, trees })
+        .collect();
+
+    SlopeSearch { counts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_input;
+
+    #[test]
+    fn test_search_table() {
+        let search = search_slopes(&example_input(), 7, 2);
+
+        assert_eq!(search.counts.len(), 16);
+        assert_eq!(search.counts[0].slope, (0, 1));
+        assert_eq!(search.counts[15].slope, (7, 2));
+
+        let puzzle_slopes = [
+            ((1, 1), 2),
+            ((3, 1), 7),
+            ((5, 1), 3),
+            ((7, 1), 4),
+            ((1, 2), 2),
+        ];
+        for (slope, trees) in puzzle_slopes.iter() {
+            let count = search.counts.iter().find(|c| c.slope == *slope).unwrap();
+            assert_eq!(count.trees, *trees);
+        }
+    }
+
+    #[test]
+    fn test_search_extremes() {
+        let search = search_slopes(&example_input(), 7, 2);
+        let trees = |slope| {
+            search
+                .counts
+                .iter()
+                .find(|c| c.slope == slope)
+                .unwrap()
+                .trees
+        };
+
+        assert_eq!(search.fewest_trees(), vec![(5, 2)]);
+        assert_eq!(trees((5, 2)), 0);
+        assert_eq!(search.most_trees(), vec![(3, 1)]);
+        assert_eq!(trees((3, 1)), 7);
+    }
+
+    #[test]
+    fn test_search_with_rocks_matches_traversal() {
+        let mut input = example_input();
+        for &(column, row) in &[(2, 2), (6, 2), (3, 3), (4, 4)] {
+            *input.get_mut(column, row).unwrap() = Map::Rock;
+        }
+        let search = search_slopes(&input, 7, 2);
+
+        for count in &search.counts {
+            let (right, down) = count.slope;
+            let traversal = Traversal::new((right as isize, down as isize));
+
+            assert_eq!(
+                count.trees,
+                traversal.count_trees(&input),
+                "{:?}",
+                count.slope
+            );
+        }
+
+        // Indexing would have treated the rocks as open squares
+        let slopes: Vec<_> = search.counts.iter().map(|c| c.slope).collect();
+        let trees: Vec<_> = search.counts.iter().map(|c| c.trees).collect();
+        assert_ne!(TreeIndex::new(&input).count_many(&slopes), trees);
+    }
+
+    #[test]
+    fn test_empty_search() {
+        let search = search_slopes(&example_input(), 3, 0);
+
+        assert!(search.counts.is_empty());
+        assert!(search.fewest_trees().is_empty());
+    }
+}
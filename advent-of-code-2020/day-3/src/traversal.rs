@@ -0,0 +1,336 @@
+use std::collections::BTreeMap;
+
+use common::grid::Grid;
+
+use crate::legend::Legend;
+use crate::{Map, TerrainRules};
+
+/// What happens when the toboggan reaches an edge of the map
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wrap {
+    /// The traversal ends at any edge
+    Stop,
+    /// The map repeats to the left and right, as in the puzzle
+    Horizontal,
+    /// The map repeats above and below
+    Vertical,
+    /// The map repeats in every direction
+    Toroidal,
+}
+
+impl Wrap {
+    fn horizontal(self) -> bool {
+        matches!(self, Wrap::Horizontal | Wrap::Toroidal)
+    }
+
+    fn vertical(self) -> bool {
+        matches!(self, Wrap::Vertical | Wrap::Toroidal)
+    }
+}
+
+/// A straight line path across the map
+///
+/// The slope is `(right, down)` and either part may be negative. The start
+/// square itself is never visited, matching the puzzle. A traversal ends when
+/// it leaves the map across an edge that does not wrap, or when it gets back to
+/// where it started, which is the only way a wrapping path can end.
+///
+/// Terrain changes the ride along the way: see `Map` for what each kind does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Traversal {
+    pub slope: (isize, isize),
+    pub start: (usize, usize),
+    pub wrap: Wrap,
+}
+
+impl Traversal {
+    /// Creates the puzzle's traversal: from the top left with horizontal wrapping
+    pub fn new(slope: (isize, isize)) -> Self {
+        Traversal {
+            slope,
+            start: (0, 0),
+            wrap: Wrap::Horizontal,
+        }
+    }
+
+    /// Iterates over every position visited on the map, in order
+    pub fn path<T>(&self, map: &Grid<T>) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = (map.width() as isize, map.height() as isize);
+        let start = (self.start.0 as isize, self.start.1 as isize);
+        let (right, down) = self.slope;
+        let wrap = self.wrap;
+
+        let mut position = if map.get(self.start.0, self.start.1).is_some() {
+            Some(start)
+        } else {
+            None
+        };
+
+        std::iter::from_fn(move || {
+            let (column, row) = position?;
+            let mut next = (column + right, row + down);
+
+            if wrap.horizontal() {
+                next.0 = next.0.rem_euclid(width);
+            }
+            if wrap.vertical() {
+                next.1 = next.1.rem_euclid(height);
+            }
+
+            let inside = (0..width).contains(&next.0) && (0..height).contains(&next.1);
+            position = if inside && next != start {
+                Some(next)
+            } else {
+                None
+            };
+
+            position.map(|(column, row)| (column as usize, row as usize))
+        })
+    }
+
+    /// Iterates over every square the toboggan lands on along with its terrain
+    ///
+    /// Unlike `path`, this stops at rocks and skips the squares passed over
+    /// while sliding across ice
+    pub fn visits<'a>(
+        &self,
+        map: &'a Grid<Map>,
+    ) -> impl Iterator<Item = ((usize, usize), Map)> + 'a {
+        self.visits_by(map, Map::rules)
+    }
+
+    /// Iterates over every square landed on, with terrain following the legend's rules
+    pub fn visits_with<'a>(
+        &self,
+        map: &'a Grid<Map>,
+        legend: &'a Legend,
+    ) -> impl Iterator<Item = ((usize, usize), Map)> + 'a {
+        self.visits_by(map, move |terrain| legend.rules(terrain))
+    }
+
+    fn visits_by<'a, F>(
+        &self,
+        map: &'a Grid<Map>,
+        rules: F,
+    ) -> impl Iterator<Item = ((usize, usize), Map)> + 'a
+    where
+        F: Fn(Map) -> TerrainRules + 'a,
+    {
+        let mut path = self.path(map);
+        let mut stopped = false;
+
+        std::iter::from_fn(move || {
+            if stopped {
+                return None;
+            }
+
+            let position = path.next()?;
+            let terrain = map[position];
+            let terrain_rules = rules(terrain);
+
+            stopped = terrain_rules.stops;
+            for _ in 0..terrain_rules.slide {
+                path.next();
+            }
+
+            Some((position, terrain))
+        })
+    }
+
+    /// Rides the path and collects statistics for every kind of terrain
+    pub fn traverse(&self, map: &Grid<Map>) -> TraversalStats {
+        self.traverse_with(map, &Legend::default())
+    }
+
+    /// Rides the path with terrain following the legend's rules
+    pub fn traverse_with(&self, map: &Grid<Map>, legend: &Legend) -> TraversalStats {
+        let mut stats = TraversalStats::default();
+
+        for (position, terrain) in self.visits_with(map, legend) {
+            let terrain_rules = legend.rules(terrain);
+
+            *stats.visits.entry(terrain).or_insert(0) += 1;
+            stats.cost += terrain_rules.cost;
+
+            if terrain_rules.stops {
+                stats.stopped_at = Some(position);
+            }
+        }
+
+        stats
+    }
+
+    /// Counts the trees hit along the path
+    pub fn count_trees(&self, map: &Grid<Map>) -> u64 {
+        self.traverse(map).visits(Map::Tree)
+    }
+}
+
+/// What happened over the course of a traversal
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TraversalStats {
+    /// The number of squares landed on for each kind of terrain
+    pub visits: BTreeMap<Map, u64>,
+    /// The total cost of every square landed on
+    pub cost: u64,
+    /// Where the toboggan was stopped, if it did not reach the end of the path
+    pub stopped_at: Option<(usize, usize)>,
+}
+
+impl TraversalStats {
+    /// The number of squares of the terrain that were landed on
+    pub fn visits(&self, terrain: Map) -> u64 {
+        self.visits.get(&terrain).copied().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::legend::Legend;
+    use crate::tests::small_map;
+    use crate::{parse_map_with_legend, ParseMode};
+
+    #[test]
+    fn test_puzzle_traversal() {
+        let map = small_map();
+        let path: Vec<(usize, usize)> = Traversal::new((2, 1)).path(&map).collect();
+
+        assert_eq!(path, vec![(2, 1), (1, 2), (0, 3)]);
+        assert_eq!(Traversal::new((2, 1)).count_trees(&map), 1);
+    }
+
+    #[test]
+    fn test_negative_slopes() {
+        let map = small_map();
+        let up_left = Traversal {
+            slope: (-1, -1),
+            start: (2, 3),
+            wrap: Wrap::Stop,
+        };
+
+        assert_eq!(up_left.path(&map).collect::<Vec<_>>(), vec![(1, 2), (0, 1)]);
+
+        let left = Traversal {
+            slope: (-1, 1),
+            ..Traversal::new((0, 0))
+        };
+
+        assert_eq!(
+            left.path(&map).collect::<Vec<_>>(),
+            vec![(2, 1), (1, 2), (0, 3)]
+        );
+        assert_eq!(left.count_trees(&map), 1);
+    }
+
+    #[test]
+    fn test_stop_at_edges() {
+        let map = small_map();
+        let traversal = Traversal {
+            slope: (1, 1),
+            start: (1, 0),
+            wrap: Wrap::Stop,
+        };
+
+        assert_eq!(traversal.path(&map).collect::<Vec<_>>(), vec![(2, 1)]);
+    }
+
+    #[test]
+    fn test_vertical_wrapping() {
+        let map = small_map();
+        let traversal = Traversal {
+            slope: (0, -1),
+            start: (0, 1),
+            wrap: Wrap::Vertical,
+        };
+
+        assert_eq!(
+            traversal.path(&map).collect::<Vec<_>>(),
+            vec![(0, 0), (0, 3), (0, 2)]
+        );
+        assert_eq!(traversal.count_trees(&map), 2);
+    }
+
+    #[test]
+    fn test_toroidal_wrapping_ends_at_start() {
+        let map = small_map();
+        let traversal = Traversal {
+            slope: (1, 1),
+            start: (0, 0),
+            wrap: Wrap::Toroidal,
+        };
+        let path: Vec<(usize, usize)> = traversal.path(&map).collect();
+
+        assert_eq!(path.len(), 11);
+        assert_eq!(path[0], (1, 1));
+        assert_eq!(path[10], (2, 3));
+    }
+
+    #[test]
+    fn test_empty_paths() {
+        let map = small_map();
+
+        assert_eq!(Traversal::new((0, 0)).path(&map).count(), 0);
+        assert_eq!(
+            Traversal {
+                start: (5, 5),
+                ..Traversal::new((1, 1))
+            }
+            .path(&map)
+            .count(),
+            0
+        );
+    }
+
+    fn terrain_map() -> Grid<Map> {
+        let legend = Legend::parse(". open\n# tree\nR rock\n~ ice\n* snow").unwrap();
+
+        parse_map_with_legend(
+            ".....\n.~...\n..#..\n...*.\n....R\n.#...",
+            ParseMode::Strict,
+            &legend,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_traverse_terrain() {
+        let map = terrain_map();
+        let stats = Traversal::new((1, 1)).traverse(&map);
+
+        assert_eq!(stats.visits(Map::Ice), 1);
+        assert_eq!(stats.visits(Map::Tree), 0);
+        assert_eq!(stats.visits(Map::Snow), 1);
+        assert_eq!(stats.visits(Map::Rock), 1);
+        assert_eq!(stats.visits(Map::Open), 0);
+        assert_eq!(stats.cost, 4);
+        assert_eq!(stats.stopped_at, Some((4, 4)));
+    }
+
+    #[test]
+    fn test_traverse_with_legend_rules() {
+        let map = terrain_map();
+        let legend =
+            Legend::parse(". open\n# tree\nR rock stop=false\n~ ice slide=0\n* snow cost=5")
+                .unwrap();
+        let stats = Traversal::new((1, 1)).traverse_with(&map, &legend);
+
+        for terrain in Map::ALL.iter() {
+            assert_eq!(stats.visits(*terrain), 1, "{:?}", terrain);
+        }
+        assert_eq!(stats.cost, 9);
+        assert_eq!(stats.stopped_at, None);
+    }
+
+    #[test]
+    fn test_visits_stop_at_rocks() {
+        let map = terrain_map();
+        let visits: Vec<(usize, usize)> = Traversal::new((1, 1))
+            .visits(&map)
+            .map(|(position, _)| position)
+            .collect();
+
+        assert_eq!(visits, vec![(1, 1), (3, 3), (4, 4)]);
+        assert_eq!(Traversal::new((1, 1)).path(&map).count(), 5);
+    }
+}
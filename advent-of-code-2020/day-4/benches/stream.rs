@@ -0,0 +1,45 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use day_4::{count_valid_passports, parse_batch, stream, KeyRules};
+
+const PASSPORTS: &str = "\
+ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+hcl:#cfa07d byr:1929
+
+hcl:#ae17e1 iyr:2013
+eyr:2024
+ecl:brn pid:760753108 byr:1931
+hgt:179cm
+
+hcl:#cfa07d eyr:2025 pid:166559648
+iyr:2011 ecl:brn hgt:59in
+
+";
+
+fn large_batch(c: &mut Criterion) {
+    let input = PASSPORTS.repeat(25_000);
+    let rules = KeyRules::default();
+
+    let mut group = c.benchmark_group("large_batch");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(input.len() as u64));
+
+    group.bench_function("collected", |b| {
+        b.iter(|| {
+            let batch = parse_batch(black_box(&input)).unwrap();
+            count_valid_passports(&batch, true, rules)
+        })
+    });
+
+    group.bench_function("streamed", |b| {
+        b.iter(|| stream::count_valid(black_box(input.as_bytes()), true, rules).unwrap())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, large_batch);
+criterion_main!(benches);
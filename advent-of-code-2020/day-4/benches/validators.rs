@@ -0,0 +1,57 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use day_4::validators::{self, reference};
+
+const VALUES: &[&str] = &[
+    "#123abc",
+    "#123abz",
+    "123abc",
+    "amb",
+    "blu",
+    "zzz",
+    "000000001",
+    "0123456789",
+    "#cfa07d",
+    "oth",
+    "087499704",
+    "gry",
+    "lzr",
+    "#fffffd",
+    "186cm",
+    "",
+];
+
+fn validators(c: &mut Criterion) {
+    let mut group = c.benchmark_group("validators");
+
+    group.bench_function("regex", |b| {
+        b.iter(|| {
+            VALUES
+                .iter()
+                .filter(|value| {
+                    reference::is_hair_color(black_box(value))
+                        || reference::is_eye_color(black_box(value))
+                        || reference::is_passport_id(black_box(value))
+                })
+                .count()
+        })
+    });
+
+    group.bench_function("hand_written", |b| {
+        b.iter(|| {
+            VALUES
+                .iter()
+                .filter(|value| {
+                    validators::is_hair_color(black_box(value))
+                        || validators::is_eye_color(black_box(value))
+                        || validators::is_passport_id(black_box(value))
+                })
+                .count()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, validators);
+criterion_main!(benches);
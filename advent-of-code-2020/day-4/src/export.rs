@@ -0,0 +1,325 @@
+//! Writes a batch of passports out as JSON, CSV or canonical text
+//!
+//! Every format lists the passport fields in the same fixed order, `byr` to
+//! `cid`, followed by any unknown keys in the order they were first seen. Text
+//! keeps every value of a repeated key, right after the first, so a passport
+//! read back from it is judged the same under any `KeyRules`. JSON and CSV hold
+//! one value per key, so only the first value of a repeated key is written.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::str::FromStr;
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+use crate::passport::Field;
+use crate::{is_passport_valid_with, KeyRules};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// An array of objects mapping each key to its value
+    Json,
+    /// A header row followed by a row per passport, with a column per key
+    Csv,
+    /// A batch file with each passport on one line, separated by blank lines
+    Text,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "text" => Ok(Format::Text),
+            other => Err(format!(
+                "unknown format '{}', expected json, csv or text",
+                other
+            )),
+        }
+    }
+}
+
+/// Which passports are written, judged by the puzzle's part two rules and the
+/// rules for duplicate and unknown keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    All,
+    Valid,
+    Invalid,
+}
+
+impl Filter {
+    fn includes(self, fields: &[(String, String)], rules: KeyRules) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Valid => is_passport_valid_with(fields, true, rules),
+            Filter::Invalid => !is_passport_valid_with(fields, true, rules),
+        }
+    }
+}
+
+/// Lists every key in the batch, passport fields first and then unknown keys
+fn columns(batch: &[&Vec<(String, String)>]) -> Vec<String> {
+    let mut columns: Vec<String> = Field::REQUIRED
+        .iter()
+        .chain(&[Field::CountryId])
+        .map(|field| field.key().to_owned())
+        .collect();
+
+    for (key, _) in batch.iter().copied().flatten() {
+        if !columns.contains(key) {
+            columns.push(key.to_owned());
+        }
+    }
+
+    columns
+}
+
+/// Orders the fields of a passport by column, keeping repeated keys in order
+fn ordered<'a>(fields: &'a [(String, String)], columns: &'a [String]) -> Vec<(&'a str, &'a str)> {
+    let mut values: Vec<(usize, &str, &str)> = fields
+        .iter()
+        .map(|(key, value)| {
+            let column = columns.iter().position(|column| column == key).unwrap();
+            (column, key.as_str(), value.as_str())
+        })
+        .collect();
+
+    values.sort_by_key(|&(column, _, _)| column);
+    values
+        .into_iter()
+        .map(|(_, key, value)| (key, value))
+        .collect()
+}
+
+/// Orders the fields of a passport by column, dropping repeated keys
+fn first_values<'a>(
+    fields: &'a [(String, String)],
+    columns: &'a [String],
+) -> Vec<(&'a str, &'a str)> {
+    let mut seen = HashSet::new();
+    let mut values = ordered(fields, columns);
+
+    values.retain(|(key, _)| seen.insert(*key));
+    values
+}
+
+/// The fields of a passport in column order, written as a JSON object
+struct Row<'a>(Vec<(&'a str, &'a str)>);
+
+impl Serialize for Row<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in &self.0 {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Quotes a CSV cell if it contains a comma, quote or line break
+fn csv_cell(value: &str) -> String {
+    if value.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+/// Writes the passports of the batch that pass the filter under the key rules
+pub fn export(
+    batch: &[Vec<(String, String)>],
+    format: Format,
+    filter: Filter,
+    rules: KeyRules,
+) -> String {
+    let passports: Vec<&Vec<(String, String)>> = batch
+        .iter()
+        .filter(|fields| filter.includes(fields, rules))
+        .collect();
+    let columns = columns(&passports);
+    let mut output = String::new();
+
+    match format {
+        Format::Json => {
+            let rows: Vec<Row> = passports
+                .iter()
+                .map(|fields| Row(first_values(fields, &columns)))
+                .collect();
+
+            writeln!(output, "{}", serde_json::to_string_pretty(&rows).unwrap()).unwrap();
+        }
+        Format::Csv => {
+            let header: Vec<String> = columns.iter().map(|column| csv_cell(column)).collect();
+            writeln!(output, "{}", header.join(",")).unwrap();
+
+            for fields in &passports {
+                let values = first_values(fields, &columns);
+                let row: Vec<String> = columns
+                    .iter()
+                    .map(|column| {
+                        values
+                            .iter()
+                            .find(|(key, _)| key == column)
+                            .map_or_else(String::new, |(_, value)| csv_cell(value))
+                    })
+                    .collect();
+
+                writeln!(output, "{}", row.join(",")).unwrap();
+            }
+        }
+        Format::Text => {
+            for (index, fields) in passports.iter().enumerate() {
+                let pairs: Vec<String> = ordered(fields, &columns)
+                    .into_iter()
+                    .map(|(key, value)| format!("{}:{}", key, value))
+                    .collect();
+
+                if index > 0 {
+                    output.push('\n');
+                }
+                writeln!(output, "{}", pairs.join(" ")).unwrap();
+            }
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{self, DefectRates};
+    use crate::{count_valid_passports, parse_batch, puzzle_two_solution, KeyPolicy};
+
+    fn batch() -> Vec<Vec<(String, String)>> {
+        parse_batch(
+            "hgt:183cm ecl:gry pid:860033327 eyr:2020 hcl:#fffffd byr:1937 iyr:2017 cid:147\n\
+             \n\
+             note:\"a,b\" iyr:2013 ecl:amb pid:028048884 byr:1929 byr:1930\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_export_text() {
+        assert_eq!(
+            export(&batch(), Format::Text, Filter::All, KeyRules::default()),
+            "byr:1937 iyr:2017 eyr:2020 hgt:183cm hcl:#fffffd ecl:gry pid:860033327 cid:147\n\
+             \n\
+             byr:1929 byr:1930 iyr:2013 ecl:amb pid:028048884 note:\"a,b\"\n"
+        );
+    }
+
+    #[test]
+    fn test_export_csv() {
+        assert_eq!(
+            export(&batch(), Format::Csv, Filter::All, KeyRules::default()),
+            "byr,iyr,eyr,hgt,hcl,ecl,pid,cid,note\n\
+             1937,2017,2020,183cm,#fffffd,gry,860033327,147,\n\
+             1929,2013,,,,amb,028048884,,\"\"\"a,b\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_export_json() {
+        let output = export(&batch(), Format::Json, Filter::All, KeyRules::default());
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(json.as_array().unwrap().len(), 2);
+        assert_eq!(json[0]["hgt"], "183cm");
+        assert_eq!(json[1]["byr"], "1929");
+        assert_eq!(json[1]["note"], "\"a,b\"");
+        assert!(output.starts_with("[\n  {\n    \"byr\": \"1937\",\n    \"iyr\": \"2017\","));
+    }
+
+    #[test]
+    fn test_export_filters() {
+        assert_eq!(
+            export(&batch(), Format::Text, Filter::Valid, KeyRules::default()),
+            "byr:1937 iyr:2017 eyr:2020 hgt:183cm hcl:#fffffd ecl:gry pid:860033327 cid:147\n"
+        );
+        assert_eq!(
+            export(&batch(), Format::Text, Filter::Invalid, KeyRules::default()),
+            "byr:1929 byr:1930 iyr:2013 ecl:amb pid:028048884 note:\"a,b\"\n"
+        );
+        assert_eq!(
+            export(&[], Format::Json, Filter::All, KeyRules::default()),
+            "[]\n"
+        );
+        assert_eq!(
+            export(&batch(), Format::Csv, Filter::Invalid, KeyRules::default())
+                .lines()
+                .next(),
+            Some("byr,iyr,eyr,hgt,hcl,ecl,pid,cid,note")
+        );
+    }
+
+    #[test]
+    fn test_filters_follow_key_rules() {
+        let batch = parse_batch(
+            "byr:1980 byr:1850 iyr:2012 eyr:2030 hgt:74in hcl:#623a2f ecl:grn pid:087499704\n",
+        )
+        .unwrap();
+        let strict = KeyRules {
+            duplicate: KeyPolicy::Strict,
+            ..KeyRules::default()
+        };
+        let invalid = export(&batch, Format::Text, Filter::Invalid, strict);
+
+        assert_eq!(
+            export(&batch, Format::Text, Filter::Valid, KeyRules::default())
+                .lines()
+                .count(),
+            1
+        );
+        assert!(export(&batch, Format::Text, Filter::Valid, strict).is_empty());
+        assert!(invalid.starts_with("byr:1980 byr:1850 "));
+        assert_eq!(
+            count_valid_passports(&parse_batch(&invalid).unwrap(), true, strict),
+            count_valid_passports(&batch, true, strict)
+        );
+    }
+
+    #[test]
+    fn test_filters_agree_with_puzzle_two() {
+        let batch = generator::generate(11, 2_000, &DefectRates::uniform(0.3)).batch();
+        let valid = parse_batch(&export(
+            &batch,
+            Format::Text,
+            Filter::Valid,
+            KeyRules::default(),
+        ))
+        .unwrap();
+        let invalid = parse_batch(&export(
+            &batch,
+            Format::Text,
+            Filter::Invalid,
+            KeyRules::default(),
+        ))
+        .unwrap();
+
+        assert_eq!(valid.len() as i32, puzzle_two_solution(&batch));
+        assert_eq!(valid.len() + invalid.len(), batch.len());
+
+        let signed = parse_batch(
+            &export(&batch, Format::Text, Filter::All, KeyRules::default())
+                .replace("byr:", "byr:+"),
+        )
+        .unwrap();
+        assert!(export(&signed, Format::Text, Filter::Valid, KeyRules::default()).is_empty());
+    }
+
+    #[test]
+    fn test_text_export_round_trips() {
+        let text = export(&batch(), Format::Text, Filter::All, KeyRules::default());
+        let reparsed = parse_batch(&text).unwrap();
+
+        assert_eq!(
+            export(&reparsed, Format::Text, Filter::All, KeyRules::default()),
+            text
+        );
+    }
+}
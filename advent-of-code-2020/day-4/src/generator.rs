@@ -0,0 +1,348 @@
+//! Random passport batches with known answers
+//!
+//! Every passport starts out valid and may then be given defects, each with its
+//! own probability. The validity of each passport is worked out from the
+//! defects it was given rather than by checking it, so the batch can be used
+//! to test the validation code.
+
+use std::fmt::Write;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::passport::{EyeColor, Field};
+
+/// The probability of each kind of defect, chosen independently per passport
+///
+/// Panics during generation if a rate is not between 0 and 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DefectRates {
+    /// A required field is left out
+    pub missing_field: f64,
+    /// A year or height is outside its allowed range
+    pub out_of_range: f64,
+    /// The height has no unit or one that is not `cm` or `in`
+    pub bad_unit: f64,
+    /// A key is repeated after its first value, which is the one that counts
+    pub duplicate_key: f64,
+    /// Fields are split across lines by unusual whitespace, which changes nothing
+    pub noise: f64,
+}
+
+impl DefectRates {
+    /// Rates that only produce valid passports
+    pub fn none() -> Self {
+        DefectRates {
+            missing_field: 0.0,
+            out_of_range: 0.0,
+            bad_unit: 0.0,
+            duplicate_key: 0.0,
+            noise: 0.0,
+        }
+    }
+
+    /// The same rate for every kind of defect
+    pub fn uniform(rate: f64) -> Self {
+        DefectRates {
+            missing_field: rate,
+            out_of_range: rate,
+            bad_unit: rate,
+            duplicate_key: rate,
+            noise: rate,
+        }
+    }
+}
+
+/// A generated passport and whether the puzzle rules should accept it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedPassport {
+    pub fields: Vec<(String, String)>,
+    /// Every required field is present, as part one checks
+    pub has_required_fields: bool,
+    /// Every required field is present and valid, as part two checks
+    pub is_valid: bool,
+}
+
+/// A generated batch as parsed fields and as the text of a batch file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedBatch {
+    pub passports: Vec<GeneratedPassport>,
+    pub text: String,
+}
+
+impl GeneratedBatch {
+    pub fn batch(&self) -> Vec<Vec<(String, String)>> {
+        self.passports.iter().map(|p| p.fields.clone()).collect()
+    }
+
+    /// The answer to part one for this batch
+    pub fn expected_with_required_fields(&self) -> i32 {
+        self.passports
+            .iter()
+            .filter(|p| p.has_required_fields)
+            .count() as i32
+    }
+
+    /// The answer to part two for this batch
+    pub fn expected_valid(&self) -> i32 {
+        self.passports.iter().filter(|p| p.is_valid).count() as i32
+    }
+}
+
+/// Picks a number from one of the ranges, with equal chance for each range
+fn pick<R: Rng>(rng: &mut R, ranges: &[(u16, u16)]) -> u16 {
+    let &(low, high) = ranges.choose(rng).unwrap();
+    rng.gen_range(low..=high)
+}
+
+fn valid_value<R: Rng>(rng: &mut R, field: Field) -> String {
+    match field {
+        Field::BirthYear => pick(rng, &[(1920, 2002)]).to_string(),
+        Field::IssueYear => pick(rng, &[(2010, 2020)]).to_string(),
+        Field::ExpirationYear => pick(rng, &[(2020, 2030)]).to_string(),
+        Field::Height if rng.gen_bool(0.5) => format!("{}cm", pick(rng, &[(150, 193)])),
+        Field::Height => format!("{}in", pick(rng, &[(59, 76)])),
+        Field::HairColor => format!("#{:06x}", rng.gen_range(0..0x100_0000)),
+        Field::EyeColor => EyeColor::ALL.choose(rng).unwrap().code().to_owned(),
+        Field::PassportId => format!("{:09}", rng.gen_range(0..1_000_000_000)),
+        Field::CountryId => pick(rng, &[(1, 350)]).to_string(),
+    }
+}
+
+fn out_of_range_value<R: Rng>(rng: &mut R, field: Field) -> String {
+    match field {
+        Field::BirthYear => pick(rng, &[(1000, 1919), (2003, 9999)]).to_string(),
+        Field::IssueYear => pick(rng, &[(1000, 2009), (2021, 9999)]).to_string(),
+        Field::ExpirationYear => pick(rng, &[(1000, 2019), (2031, 9999)]).to_string(),
+        _ if rng.gen_bool(0.5) => format!("{}cm", pick(rng, &[(0, 149), (194, 999)])),
+        _ => format!("{}in", pick(rng, &[(0, 58), (77, 999)])),
+    }
+}
+
+/// Writes the fields of a passport, separated by spaces and line breaks
+fn write_passport<R: Rng>(
+    rng: &mut R,
+    output: &mut String,
+    fields: &[(String, String)],
+    noisy: bool,
+) {
+    const NOISE: &[&str] = &["  ", "\t", " \t ", "\r\n", " \n", "\n\t"];
+
+    if noisy {
+        output.push_str(NOISE.choose(rng).unwrap().trim_end_matches('\n'));
+    }
+
+    for (index, (key, value)) in fields.iter().enumerate() {
+        if index > 0 {
+            let separator = if noisy && rng.gen_bool(0.5) {
+                NOISE.choose(rng).unwrap()
+            } else if rng.gen_bool(0.25) {
+                "\n"
+            } else {
+                " "
+            };
+            output.push_str(separator);
+        }
+        write!(output, "{}:{}", key, value).unwrap();
+    }
+
+    output.push_str(if noisy { " \r\n" } else { "\n" });
+}
+
+/// Generates a passport, keeping track of whether it should be accepted
+pub fn generate_passport<R: Rng>(rng: &mut R, rates: &DefectRates) -> GeneratedPassport {
+    let mut fields: Vec<(Field, String)> = Field::REQUIRED
+        .iter()
+        .map(|&field| (field, valid_value(rng, field)))
+        .collect();
+    let mut has_required_fields = true;
+    let mut is_valid = true;
+
+    if rng.gen_bool(0.5) {
+        fields.push((Field::CountryId, valid_value(rng, Field::CountryId)));
+    }
+
+    if rng.gen_bool(rates.out_of_range) {
+        let field = *[
+            Field::BirthYear,
+            Field::IssueYear,
+            Field::ExpirationYear,
+            Field::Height,
+        ]
+        .choose(rng)
+        .unwrap();
+        let position = fields.iter().position(|(f, _)| *f == field).unwrap();
+
+        fields[position].1 = out_of_range_value(rng, field);
+        is_valid = false;
+    }
+
+    if rng.gen_bool(rates.bad_unit) {
+        let position = fields
+            .iter()
+            .position(|(f, _)| *f == Field::Height)
+            .unwrap();
+        let height = pick(rng, &[(59, 76), (150, 193)]);
+        let unit = ["", "mm", "ft", "CM", "In", "m"].choose(rng).unwrap();
+
+        fields[position].1 = format!("{}{}", height, unit);
+        is_valid = false;
+    }
+
+    if rng.gen_bool(rates.missing_field) {
+        let field = *Field::REQUIRED.choose(rng).unwrap();
+
+        fields.retain(|(f, _)| *f != field);
+        has_required_fields = false;
+        is_valid = false;
+    }
+
+    fields.shuffle(rng);
+
+    if rng.gen_bool(rates.duplicate_key) {
+        let field = fields.choose(rng).unwrap().0;
+        let value = if rng.gen_bool(0.5) {
+            valid_value(rng, field)
+        } else {
+            "invalid".to_owned()
+        };
+
+        // Only the first value of a key counts, so this is placed after it
+        let first = fields.iter().position(|(f, _)| *f == field).unwrap();
+        let position = rng.gen_range(first + 1..=fields.len());
+        fields.insert(position, (field, value));
+    }
+
+    GeneratedPassport {
+        fields: fields
+            .into_iter()
+            .map(|(field, value)| (field.key().to_owned(), value))
+            .collect(),
+        has_required_fields,
+        is_valid,
+    }
+}
+
+/// Generates `count` passports along with the text of their batch file
+pub fn generate_batch<R: Rng>(rng: &mut R, count: usize, rates: &DefectRates) -> GeneratedBatch {
+    let mut passports = Vec::with_capacity(count);
+    let mut text = String::new();
+
+    for index in 0..count {
+        let passport = generate_passport(rng, rates);
+        let noisy = rng.gen_bool(rates.noise);
+
+        if index > 0 {
+            text.push_str(if noisy { "\n \t\r\n\n" } else { "\n" });
+        }
+        write_passport(rng, &mut text, &passport.fields, noisy);
+        passports.push(passport);
+    }
+
+    GeneratedBatch { passports, text }
+}
+
+/// Generates a batch from a seed
+pub fn generate(seed: u64, count: usize, rates: &DefectRates) -> GeneratedBatch {
+    generate_batch(&mut StdRng::seed_from_u64(seed), count, rates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        count_valid_passports, is_passport_valid, parse_batch, puzzle_one_solution,
+        puzzle_two_solution, stream, KeyRules,
+    };
+
+    #[test]
+    fn test_text_parses_to_fields() {
+        for seed in 0..50 {
+            let generated = generate(seed, 200, &DefectRates::uniform(0.3));
+
+            assert_eq!(parse_batch(&generated.text).unwrap(), generated.batch());
+        }
+    }
+
+    #[test]
+    fn test_solutions_match_ground_truth() {
+        let mut rng = StdRng::seed_from_u64(4);
+
+        for seed in 0..20 {
+            let rates = DefectRates {
+                missing_field: rng.gen_range(0.0..=0.5),
+                out_of_range: rng.gen_range(0.0..=0.5),
+                bad_unit: rng.gen_range(0.0..=0.5),
+                duplicate_key: rng.gen_range(0.0..=0.5),
+                noise: rng.gen_range(0.0..=1.0),
+            };
+            let generated = generate(seed, 2_000, &rates);
+            let batch = parse_batch(&generated.text).unwrap();
+            let expected = (
+                generated.expected_with_required_fields(),
+                generated.expected_valid(),
+            );
+
+            assert_eq!(
+                (puzzle_one_solution(&batch), puzzle_two_solution(&batch)),
+                expected,
+                "{:?}",
+                rates
+            );
+
+            let rules = KeyRules::default();
+            let streamed = (
+                stream::count_valid(generated.text.as_bytes(), false, rules).unwrap() as i32,
+                stream::count_valid(generated.text.as_bytes(), true, rules).unwrap() as i32,
+            );
+            assert_eq!(streamed, expected, "{:?}", rates);
+        }
+    }
+
+    #[test]
+    fn test_each_passport_matches_ground_truth() {
+        let generated = generate(2020, 10_000, &DefectRates::uniform(0.2));
+
+        for passport in &generated.passports {
+            assert_eq!(
+                is_passport_valid(&passport.fields, false),
+                passport.has_required_fields,
+                "{:?}",
+                passport.fields
+            );
+            assert_eq!(
+                is_passport_valid(&passport.fields, true),
+                passport.is_valid,
+                "{:?}",
+                passport.fields
+            );
+        }
+
+        // Every outcome is produced at this rate
+        assert!(generated.expected_valid() > 0);
+        assert!(generated.expected_with_required_fields() > generated.expected_valid());
+        assert!(generated.expected_with_required_fields() < 10_000);
+    }
+
+    #[test]
+    fn test_defect_free_batches() {
+        let generated = generate(7, 1_000, &DefectRates::none());
+        let batch = generated.batch();
+
+        assert_eq!(generated.expected_valid(), 1_000);
+        assert_eq!(
+            count_valid_passports(&batch, true, KeyRules::default()),
+            1_000
+        );
+        assert!(!generated.text.contains('\r'));
+    }
+
+    #[test]
+    fn test_generation_is_seeded() {
+        let rates = DefectRates::uniform(0.25);
+
+        assert_eq!(generate(1, 100, &rates), generate(1, 100, &rates));
+        assert_ne!(generate(1, 100, &rates).text, generate(2, 100, &rates).text);
+    }
+}
@@ -0,0 +1,632 @@
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs;
+
+use common::records::{records, RecordError};
+
+pub mod export;
+pub mod generator;
+pub mod passport;
+pub mod repair;
+pub mod report;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod stream;
+pub mod validators;
+
+use passport::{Field, FieldError, Passport};
+#[cfg(feature = "schema")]
+use schema::Schema;
+
+/// Parses input into a vector of vector of tuples
+pub fn parse_input(filename: &str) -> Vec<Vec<(String, String)>> {
+    let input = match fs::read_to_string(filename) {
+        Ok(input) => input,
+        Err(e) => panic!("Error reading file: {}", e),
+    };
+
+    match parse_batch(&input) {
+        Ok(batch) => batch,
+        Err(e) => panic!("Error parsing passports: {}", e),
+    }
+}
+
+/// Parses the text of a batch file, where passports are separated by blank lines
+pub fn parse_batch(input: &str) -> Result<Vec<Vec<(String, String)>>, RecordError> {
+    records(input)
+        .map(|record| {
+            let fields = record.fields()?;
+
+            Ok(fields
+                .into_iter()
+                .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                .collect())
+        })
+        .collect()
+}
+
+/// Writes a batch file with every field of each passport in its original order
+///
+/// This is the reverse of `parse_batch`, which reads the output back unchanged.
+pub fn write_batch(batch: &[Vec<(String, String)>]) -> String {
+    let passports: Vec<String> = batch
+        .iter()
+        .map(|fields| {
+            let pairs: Vec<String> = fields
+                .iter()
+                .map(|(key, value)| format!("{}:{}", key, value))
+                .collect();
+
+            pairs.join(" ") + "\n"
+        })
+        .collect();
+
+    passports.join("\n")
+}
+
+/// How a passport with an irregular key is treated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyPolicy {
+    /// The passport is invalid
+    Strict,
+    /// The passport is checked as if the key was not there
+    Lenient,
+}
+
+/// The policies for keys that are repeated and keys that are not passport fields
+///
+/// When duplicates are allowed, only the first value given for a key is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyRules {
+    pub duplicate: KeyPolicy,
+    pub unknown: KeyPolicy,
+}
+
+impl Default for KeyRules {
+    /// Allows both, which is what the puzzle expects
+    fn default() -> Self {
+        KeyRules {
+            duplicate: KeyPolicy::Lenient,
+            unknown: KeyPolicy::Lenient,
+        }
+    }
+}
+
+/// Describes a key that appears more than once or is not a passport field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyError {
+    Duplicate { key: String },
+    Unknown { key: String },
+}
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeyError::Duplicate { key } => write!(f, "{} is given more than once", key),
+            KeyError::Unknown { key } => write!(f, "{} is not a passport field", key),
+        }
+    }
+}
+
+impl std::error::Error for KeyError {}
+
+/// Finds every repeat of a key and every key that is not a passport field
+pub fn key_errors<K: AsRef<str>, V>(fields: &[(K, V)]) -> Vec<KeyError> {
+    let mut seen = HashSet::new();
+    let mut errors = Vec::new();
+
+    for key in fields.iter().map(|(key, _)| key.as_ref()) {
+        if Field::from_key(key).is_none() {
+            errors.push(KeyError::Unknown {
+                key: key.to_owned(),
+            });
+        } else if !seen.insert(key) {
+            errors.push(KeyError::Duplicate {
+                key: key.to_owned(),
+            });
+        }
+    }
+
+    errors
+}
+
+/// Tests if a function is valid based on the number of fields
+/// If the strict parameter is set to true, then it validates the fields as well
+pub fn is_passport_valid(fields: &[(String, String)], strict: bool) -> bool {
+    is_passport_valid_with(fields, strict, KeyRules::default())
+}
+
+/// Tests if a passport is valid, applying the rules for duplicate and unknown keys
+///
+/// Fields may be owned or borrowed, so passports read by `stream` are checked without copying.
+pub fn is_passport_valid_with<K: AsRef<str>, V: AsRef<str>>(
+    fields: &[(K, V)],
+    strict: bool,
+    rules: KeyRules,
+) -> bool {
+    if fields.len() < 7 {
+        return false;
+    }
+
+    let rejected = key_errors(fields).iter().any(|error| match error {
+        KeyError::Duplicate { .. } => rules.duplicate == KeyPolicy::Strict,
+        KeyError::Unknown { .. } => rules.unknown == KeyPolicy::Strict,
+    });
+
+    if rejected || count_fields(fields) != 7 {
+        return false;
+    }
+
+    // Only a passport whose values all convert to their types is valid
+    !strict || Passport::try_from(fields).is_ok()
+}
+
+/// Counts the number of distinct required fields, excluding "cid" and unknown keys
+pub fn count_fields<K: AsRef<str>, V>(fields: &[(K, V)]) -> i32 {
+    Field::REQUIRED
+        .iter()
+        .filter(|field| fields.iter().any(|(key, _)| key.as_ref() == field.key()))
+        .count() as i32
+}
+
+/// Validates the value of a single field, explaining why it is invalid
+/// Fields that have no requirements, such as "cid" or unknown keys, always pass
+pub fn check_field(key: &str, value: &str) -> Result<(), FieldError> {
+    match Field::from_key(key) {
+        Some(field) => field.check(value),
+        None => Ok(()),
+    }
+}
+
+/// Lists the reason every invalid value in the passport fails validation
+/// Missing fields are not reported, since those are found by `count_fields`
+pub fn field_errors(fields: &[(String, String)]) -> Vec<FieldError> {
+    fields
+        .iter()
+        .filter_map(|(key, value)| check_field(key, value).err())
+        .collect()
+}
+
+/// Counts the valid passports in the batch, applying the rules for duplicate and unknown keys
+pub fn count_valid_passports(
+    batch: &[Vec<(String, String)>],
+    strict: bool,
+    rules: KeyRules,
+) -> i32 {
+    let mut valid_count = 0;
+
+    for fields in batch {
+        if is_passport_valid_with(fields, strict, rules) {
+            valid_count += 1;
+        }
+    }
+
+    valid_count
+}
+
+pub fn puzzle_one_solution(batch: &[Vec<(String, String)>]) -> i32 {
+    count_valid_passports(batch, false, KeyRules::default())
+}
+
+pub fn puzzle_two_solution(batch: &[Vec<(String, String)>]) -> i32 {
+    count_valid_passports(batch, true, KeyRules::default())
+}
+
+/// Counts the passports with every field the schema requires
+/// If the strict parameter is set to true, then every value must satisfy the schema as well
+#[cfg(feature = "schema")]
+pub fn count_valid_with_schema(
+    batch: &[Vec<(String, String)>],
+    schema: &Schema,
+    strict: bool,
+) -> usize {
+    batch
+        .iter()
+        .filter(|fields| {
+            if strict {
+                schema.is_valid(fields)
+            } else {
+                schema.has_required_fields(fields)
+            }
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses the fields of a single passport, shared by the tests of every module
+    pub(crate) fn fields(input: &str) -> Vec<(String, String)> {
+        parse_batch(input).unwrap().remove(0)
+    }
+
+    fn get_example_input() -> Vec<Vec<(String, String)>> {
+        vec![
+            vec![
+                ("ecl".to_owned(), "gry".to_owned()),
+                ("pid".to_owned(), "860033327".to_owned()),
+                ("eyr".to_owned(), "2020".to_owned()),
+                ("hcl".to_owned(), "#fffffd".to_owned()),
+                ("byr".to_owned(), "1937".to_owned()),
+                ("iyr".to_owned(), "2017".to_owned()),
+                ("cid".to_owned(), "147".to_owned()),
+                ("hgt".to_owned(), "183cm".to_owned()),
+            ],
+            vec![
+                ("iyr".to_owned(), "2013".to_owned()),
+                ("ecl".to_owned(), "amb".to_owned()),
+                ("cid".to_owned(), "350".to_owned()),
+                ("eyr".to_owned(), "2023".to_owned()),
+                ("pid".to_owned(), "028048884".to_owned()),
+                ("hcl".to_owned(), "#cfa07d".to_owned()),
+                ("byr".to_owned(), "1929".to_owned()),
+            ],
+            vec![
+                ("hcl".to_owned(), "#ae17e1".to_owned()),
+                ("iyr".to_owned(), "2013".to_owned()),
+                ("eyr".to_owned(), "2024".to_owned()),
+                ("ecl".to_owned(), "brn".to_owned()),
+                ("pid".to_owned(), "760753108".to_owned()),
+                ("byr".to_owned(), "1931".to_owned()),
+                ("hgt".to_owned(), "179cm".to_owned()),
+            ],
+            vec![
+                ("hcl".to_owned(), "#cfa07d".to_owned()),
+                ("eyr".to_owned(), "2025".to_owned()),
+                ("pid".to_owned(), "166559648".to_owned()),
+                ("iyr".to_owned(), "2011".to_owned()),
+                ("ecl".to_owned(), "brn".to_owned()),
+                ("hgt".to_owned(), "59in".to_owned()),
+            ],
+        ]
+    }
+
+    fn get_example_invalid_input() -> Vec<Vec<(String, String)>> {
+        vec![
+            vec![
+                ("eyr".to_owned(), "1972".to_owned()),
+                ("cid".to_owned(), "100".to_owned()),
+                ("hcl".to_owned(), "#18171d".to_owned()),
+                ("ecl".to_owned(), "amb".to_owned()),
+                ("hgt".to_owned(), "170".to_owned()),
+                ("pid".to_owned(), "186cm".to_owned()),
+                ("iyr".to_owned(), "2018".to_owned()),
+                ("byr".to_owned(), "1926".to_owned()),
+            ],
+            vec![
+                ("iyr".to_owned(), "2019".to_owned()),
+                ("hcl".to_owned(), "#602927".to_owned()),
+                ("eyr".to_owned(), "1967".to_owned()),
+                ("hgt".to_owned(), "170cm".to_owned()),
+                ("ecl".to_owned(), "grn".to_owned()),
+                ("pid".to_owned(), "012533040".to_owned()),
+                ("byr".to_owned(), "1946".to_owned()),
+            ],
+            vec![
+                ("hcl".to_owned(), "dab227".to_owned()),
+                ("iyr".to_owned(), "2012".to_owned()),
+                ("ecl".to_owned(), "brn".to_owned()),
+                ("hgt".to_owned(), "182cm".to_owned()),
+                ("pid".to_owned(), "021572410".to_owned()),
+                ("eyr".to_owned(), "2020".to_owned()),
+                ("byr".to_owned(), "1992".to_owned()),
+                ("cid".to_owned(), "277".to_owned()),
+            ],
+            vec![
+                ("hgt".to_owned(), "59cm".to_owned()),
+                ("ecl".to_owned(), "zzz".to_owned()),
+                ("eyr".to_owned(), "2038".to_owned()),
+                ("hcl".to_owned(), "74454a".to_owned()),
+                ("iyr".to_owned(), "2023".to_owned()),
+                ("pid".to_owned(), "3556412378".to_owned()),
+                ("byr".to_owned(), "2007".to_owned()),
+            ],
+        ]
+    }
+
+    fn get_example_all_valid_input() -> Vec<Vec<(String, String)>> {
+        vec![
+            vec![
+                ("pid".to_owned(), "087499704".to_owned()),
+                ("hgt".to_owned(), "74in".to_owned()),
+                ("ecl".to_owned(), "grn".to_owned()),
+                ("iyr".to_owned(), "2012".to_owned()),
+                ("eyr".to_owned(), "2030".to_owned()),
+                ("byr".to_owned(), "1980".to_owned()),
+                ("hcl".to_owned(), "#623a2f".to_owned()),
+            ],
+            vec![
+                ("eyr".to_owned(), "2029".to_owned()),
+                ("ecl".to_owned(), "blu".to_owned()),
+                ("cid".to_owned(), "129".to_owned()),
+                ("byr".to_owned(), "1989".to_owned()),
+                ("iyr".to_owned(), "2014".to_owned()),
+                ("pid".to_owned(), "896056539".to_owned()),
+                ("hcl".to_owned(), "#a97842".to_owned()),
+                ("hgt".to_owned(), "165cm".to_owned()),
+            ],
+            vec![
+                ("hcl".to_owned(), "#888785".to_owned()),
+                ("hgt".to_owned(), "164cm".to_owned()),
+                ("byr".to_owned(), "2001".to_owned()),
+                ("iyr".to_owned(), "2015".to_owned()),
+                ("cid".to_owned(), "88".to_owned()),
+                ("pid".to_owned(), "545766238".to_owned()),
+                ("ecl".to_owned(), "hzl".to_owned()),
+                ("eyr".to_owned(), "2022".to_owned()),
+            ],
+            vec![
+                ("iyr".to_owned(), "2010".to_owned()),
+                ("hgt".to_owned(), "158cm".to_owned()),
+                ("hcl".to_owned(), "#b6652a".to_owned()),
+                ("ecl".to_owned(), "blu".to_owned()),
+                ("byr".to_owned(), "1944".to_owned()),
+                ("eyr".to_owned(), "2021".to_owned()),
+                ("pid".to_owned(), "093154719".to_owned()),
+            ],
+        ]
+    }
+    #[test]
+    fn test_puzzle_one_example() {
+        let input = get_example_input();
+
+        assert_eq!(puzzle_one_solution(&input), 2);
+    }
+
+    #[test]
+    fn test_puzzle_two_invalid_input() {
+        let input = get_example_invalid_input();
+
+        assert_eq!(puzzle_two_solution(&input), 0);
+    }
+
+    #[test]
+    fn test_puzzle_two_all_valid_input() {
+        let input = get_example_all_valid_input();
+
+        assert_eq!(puzzle_two_solution(&input), 4);
+    }
+
+    /// Builds a valid passport with one field replaced by the given value
+    fn passport_with(key: &str, value: &str) -> Vec<(String, String)> {
+        let mut fields =
+            parse_batch("pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f")
+                .unwrap()
+                .remove(0);
+
+        for field in fields.iter_mut().filter(|(k, _)| k == key) {
+            field.1 = value.to_owned();
+        }
+
+        fields
+    }
+
+    /// Malformed and out of range values, including ones that used to make
+    /// validation panic, and why each is invalid
+    fn get_malformed_values() -> Vec<(&'static str, &'static str, bool)> {
+        // (key, value, out of range rather than malformed)
+        vec![
+            ("hgt", "170", false),
+            ("hgt", "cm", false),
+            ("hgt", "in", false),
+            ("hgt", "170cmx", false),
+            ("hgt", "1cmé", false),
+            ("hgt", "éin", false),
+            ("hgt", "300cm", true),
+            ("hgt", "99999in", true),
+            ("byr", "abc", false),
+            ("byr", "", false),
+            ("hgt", "+74in", false),
+            ("hgt", "-74in", false),
+            ("hgt", "74 in", false),
+            ("hgt", "99999999999999999999cm", true),
+            ("byr", "+1980", false),
+            ("byr", "01980", false),
+            ("byr", "19800", false),
+            ("iyr", "70000", false),
+            ("eyr", "-2025", false),
+            ("eyr", "20 25", false),
+            ("ecl", "grnn", true),
+            ("hcl", "#623a2f0", false),
+            ("pid", "0087499704", false),
+            ("pid", "+87499704", false),
+        ]
+    }
+
+    /// Unusual values that every validator accepts
+    fn get_accepted_values() -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("hgt", "074in"),
+            ("hgt", "0000000000000000000150cm"),
+            ("pid", "000000000"),
+        ]
+    }
+
+    #[test]
+    fn test_malformed_values_are_invalid() {
+        for (key, value, out_of_range) in get_malformed_values() {
+            let fields = passport_with(key, value);
+            let field = Field::from_key(key).unwrap();
+            let value = value.to_owned();
+            let expected = if out_of_range {
+                FieldError::OutOfRange { field, value }
+            } else {
+                FieldError::Malformed { field, value }
+            };
+
+            assert!(!is_passport_valid(&fields, true), "{:?}", fields);
+            assert!(is_passport_valid(&fields, false), "{:?}", fields);
+            assert_eq!(field_errors(&fields), vec![expected.clone()]);
+            assert_eq!(
+                Passport::try_from(fields.as_slice()).unwrap_err().errors,
+                vec![expected]
+            );
+        }
+
+        for (key, value) in get_accepted_values() {
+            let fields = passport_with(key, value);
+
+            assert!(is_passport_valid(&fields, true), "{:?}", fields);
+            assert!(field_errors(&fields).is_empty(), "{:?}", fields);
+            assert!(Passport::try_from(fields.as_slice()).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_malformed_batch() {
+        let batch: Vec<Vec<(String, String)>> = get_malformed_values()
+            .into_iter()
+            .map(|(key, value, _)| passport_with(key, value))
+            .collect();
+
+        assert_eq!(puzzle_one_solution(&batch), batch.len() as i32);
+        assert_eq!(puzzle_two_solution(&batch), 0);
+        #[cfg(feature = "schema")]
+        assert_eq!(count_valid_with_schema(&batch, &Schema::default(), true), 0);
+        assert!(batch
+            .iter()
+            .all(|fields| Passport::try_from(fields.as_slice()).is_err()));
+    }
+
+    #[test]
+    fn test_field_errors_lists_every_reason() {
+        let mut fields = passport_with("hgt", "59cm");
+        fields.push(("byr".to_owned(), "19x0".to_owned()));
+        fields.push(("cid".to_owned(), "anything".to_owned()));
+
+        assert_eq!(
+            field_errors(&fields)
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>(),
+            vec!["hgt '59cm' is out of range", "byr '19x0' is malformed"]
+        );
+        assert!(field_errors(&passport_with("hgt", "59in")).is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_keys() {
+        let mut fields = passport_with("hgt", "74in");
+        fields.retain(|(key, _)| key != "hgt");
+        fields.push(("byr".to_owned(), "1990".to_owned()));
+
+        // Seven keys, but hgt is missing
+        assert_eq!(count_fields(&fields), 6);
+        assert!(!is_passport_valid(&fields, false));
+
+        let mut fields = passport_with("byr", "1980");
+        fields.push(("byr".to_owned(), "1850".to_owned()));
+        let strict = KeyRules {
+            duplicate: KeyPolicy::Strict,
+            ..KeyRules::default()
+        };
+
+        assert_eq!(
+            key_errors(&fields),
+            vec![KeyError::Duplicate {
+                key: "byr".to_owned()
+            }]
+        );
+        assert!(is_passport_valid(&fields, true));
+        assert!(!is_passport_valid_with(&fields, false, strict));
+        assert!(!is_passport_valid_with(&fields, true, strict));
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let mut fields = passport_with("hgt", "74in");
+        fields.retain(|(key, _)| key != "hgt");
+        fields.push(("xyz".to_owned(), "1".to_owned()));
+
+        // Seven keys, but one is not a passport field
+        assert_eq!(count_fields(&fields), 6);
+        assert!(!is_passport_valid(&fields, false));
+
+        let mut fields = passport_with("hgt", "74in");
+        fields.push(("xyz".to_owned(), "1".to_owned()));
+        let strict = KeyRules {
+            unknown: KeyPolicy::Strict,
+            ..KeyRules::default()
+        };
+
+        assert_eq!(
+            key_errors(&fields),
+            vec![KeyError::Unknown {
+                key: "xyz".to_owned()
+            }]
+        );
+        assert!(is_passport_valid(&fields, true));
+        assert!(!is_passport_valid_with(&fields, false, strict));
+        assert!(!is_passport_valid_with(&fields, true, strict));
+    }
+
+    #[test]
+    fn test_count_valid_passports_with_rules() {
+        let mut batch = get_example_all_valid_input();
+        batch[0].push(("pid".to_owned(), "087499704".to_owned()));
+        batch[1].push(("abc".to_owned(), "def".to_owned()));
+        batch[1].push(("abc".to_owned(), "def".to_owned()));
+
+        let rules = |duplicate, unknown| KeyRules { duplicate, unknown };
+        let (strict, lenient) = (KeyPolicy::Strict, KeyPolicy::Lenient);
+
+        assert_eq!(
+            count_valid_passports(&batch, true, rules(lenient, lenient)),
+            4
+        );
+        assert_eq!(
+            count_valid_passports(&batch, true, rules(strict, lenient)),
+            3
+        );
+        assert_eq!(
+            count_valid_passports(&batch, true, rules(lenient, strict)),
+            3
+        );
+        assert_eq!(
+            count_valid_passports(&batch, false, rules(strict, strict)),
+            2
+        );
+    }
+
+    #[test]
+    fn test_parse_batch() {
+        let input = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd\r\n\
+                     byr:1937\tiyr:2017  cid:147 hgt:183cm\r\n\
+                     \t\r\n\
+                     iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884\n\
+                     hcl:#cfa07d byr:1929\n\
+                     \n\
+                     \n";
+        let batch = parse_batch(input).unwrap();
+
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch, get_example_input()[..2].to_vec());
+    }
+
+    #[test]
+    fn test_write_batch() {
+        let batch = parse_batch("hgt:74in byr:1980\nbyr:1850 cid:1\n\n\npid:0 zzz:a\n").unwrap();
+        let text = write_batch(&batch);
+
+        assert_eq!(text, "hgt:74in byr:1980 byr:1850 cid:1\n\npid:0 zzz:a\n");
+        assert_eq!(parse_batch(&text).unwrap(), batch);
+        assert_eq!(write_batch(&[]), "");
+    }
+
+    #[test]
+    fn test_parse_batch_errors() {
+        assert_eq!(
+            parse_batch("byr:1937\n\niyr:2017 cid\n"),
+            Err(RecordError::MissingSeparator {
+                line: 3,
+                field: "cid".to_owned()
+            })
+        );
+        assert_eq!(
+            parse_batch("url:http://a:b").unwrap(),
+            vec![vec![("url".to_owned(), "http://a:b".to_owned())]]
+        );
+    }
+}
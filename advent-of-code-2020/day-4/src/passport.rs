@@ -0,0 +1,556 @@
+//! Passports with typed and validated fields
+//!
+//! A `Passport` can only be built from fields that satisfy the puzzle's rules,
+//! so code holding one never needs to check or re-parse its values. Building
+//! one reports every problem with the fields at once rather than just the first.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+#[cfg(all(feature = "regex-validators", not(feature = "fast-validators")))]
+use crate::validators::reference::{is_eye_color, is_hair_color, is_passport_id};
+#[cfg(any(feature = "fast-validators", not(feature = "regex-validators")))]
+use crate::validators::{is_eye_color, is_hair_color, is_passport_id};
+
+const BIRTH_YEARS: RangeInclusive<u16> = 1920..=2002;
+const ISSUE_YEARS: RangeInclusive<u16> = 2010..=2020;
+const EXPIRATION_YEARS: RangeInclusive<u16> = 2020..=2030;
+
+/// The key of a passport field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Field {
+    BirthYear,
+    IssueYear,
+    ExpirationYear,
+    Height,
+    HairColor,
+    EyeColor,
+    PassportId,
+    CountryId,
+}
+
+impl Field {
+    /// Every field a passport must have, in the order they are checked
+    pub const REQUIRED: [Field; 7] = [
+        Field::BirthYear,
+        Field::IssueYear,
+        Field::ExpirationYear,
+        Field::Height,
+        Field::HairColor,
+        Field::EyeColor,
+        Field::PassportId,
+    ];
+
+    /// Looks up a field by its three letter key
+    pub fn from_key(key: &str) -> Option<Field> {
+        Field::REQUIRED
+            .iter()
+            .chain(&[Field::CountryId])
+            .copied()
+            .find(|field| field.key() == key)
+    }
+
+    pub fn key(self) -> &'static str {
+        match self {
+            Field::BirthYear => "byr",
+            Field::IssueYear => "iyr",
+            Field::ExpirationYear => "eyr",
+            Field::Height => "hgt",
+            Field::HairColor => "hcl",
+            Field::EyeColor => "ecl",
+            Field::PassportId => "pid",
+            Field::CountryId => "cid",
+        }
+    }
+
+    /// Checks a value against the field's rules without keeping what it parses to
+    ///
+    /// The country id has no rules, so any value passes
+    pub fn check(self, value: &str) -> Result<(), FieldError> {
+        let checked = match self {
+            Field::BirthYear => parse_year(value, BIRTH_YEARS).map(drop),
+            Field::IssueYear => parse_year(value, ISSUE_YEARS).map(drop),
+            Field::ExpirationYear => parse_year(value, EXPIRATION_YEARS).map(drop),
+            Field::Height => value.parse::<Height>().map(drop),
+            Field::HairColor => value.parse::<HairColor>().map(drop),
+            Field::EyeColor => value.parse::<EyeColor>().map(drop),
+            Field::PassportId => value.parse::<PassportId>().map(drop),
+            Field::CountryId => Ok(()),
+        };
+
+        checked.map_err(|error| FieldError::from_value_error(self, value, error))
+    }
+}
+
+/// Describes what is wrong with a single field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldError {
+    Missing {
+        field: Field,
+    },
+    /// The value is not written in the field's format
+    Malformed {
+        field: Field,
+        value: String,
+    },
+    /// The value is well formed but outside the range the field allows
+    OutOfRange {
+        field: Field,
+        value: String,
+    },
+}
+
+impl FieldError {
+    fn from_value_error(field: Field, value: &str, error: ValueError) -> Self {
+        let value = value.to_owned();
+
+        match error {
+            ValueError::Malformed => FieldError::Malformed { field, value },
+            ValueError::OutOfRange => FieldError::OutOfRange { field, value },
+        }
+    }
+
+    pub fn field(&self) -> Field {
+        match self {
+            FieldError::Missing { field }
+            | FieldError::Malformed { field, .. }
+            | FieldError::OutOfRange { field, .. } => *field,
+        }
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FieldError::Missing { field } => write!(f, "{} is missing", field.key()),
+            FieldError::Malformed { field, value } => {
+                write!(f, "{} '{}' is malformed", field.key(), value)
+            }
+            FieldError::OutOfRange { field, value } => {
+                write!(f, "{} '{}' is out of range", field.key(), value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FieldError {}
+
+/// Every problem found while building a passport
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassportError {
+    pub errors: Vec<FieldError>,
+}
+
+impl fmt::Display for PassportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let errors: Vec<String> = self.errors.iter().map(|e| e.to_string()).collect();
+
+        write!(f, "{}", errors.join(", "))
+    }
+}
+
+impl std::error::Error for PassportError {}
+
+/// A height along with the unit it was measured in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Height {
+    Centimeters(u16),
+    Inches(u16),
+}
+
+impl fmt::Display for Height {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Height::Centimeters(height) => write!(f, "{}cm", height),
+            Height::Inches(height) => write!(f, "{}in", height),
+        }
+    }
+}
+
+/// A hair color written as `#` followed by six lowercase hex digits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HairColor {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl fmt::Display for HairColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EyeColor {
+    Amber,
+    Blue,
+    Brown,
+    Gray,
+    Green,
+    Hazel,
+    Other,
+}
+
+impl EyeColor {
+    pub const ALL: [EyeColor; 7] = [
+        EyeColor::Amber,
+        EyeColor::Blue,
+        EyeColor::Brown,
+        EyeColor::Gray,
+        EyeColor::Green,
+        EyeColor::Hazel,
+        EyeColor::Other,
+    ];
+
+    /// The three letter code used in passport batches
+    pub fn code(self) -> &'static str {
+        match self {
+            EyeColor::Amber => "amb",
+            EyeColor::Blue => "blu",
+            EyeColor::Brown => "brn",
+            EyeColor::Gray => "gry",
+            EyeColor::Green => "grn",
+            EyeColor::Hazel => "hzl",
+            EyeColor::Other => "oth",
+        }
+    }
+}
+
+impl fmt::Display for EyeColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+/// A nine digit passport number, which may have leading zeros
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassportId(String);
+
+impl PassportId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for PassportId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Why a value could not be converted, before the field it came from is known
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueError {
+    Malformed,
+    OutOfRange,
+}
+
+impl FromStr for Height {
+    type Err = ValueError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (height, range, unit): (_, _, fn(u16) -> Height) =
+            if let Some(height) = value.strip_suffix("cm") {
+                (height, 150..=193, Height::Centimeters)
+            } else if let Some(height) = value.strip_suffix("in") {
+                (height, 59..=76, Height::Inches)
+            } else {
+                return Err(ValueError::Malformed);
+            };
+
+        let height = parse_number(height)?;
+        if range.contains(&height) {
+            Ok(unit(height))
+        } else {
+            Err(ValueError::OutOfRange)
+        }
+    }
+}
+
+impl FromStr for HairColor {
+    type Err = ValueError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if !is_hair_color(value) {
+            return Err(ValueError::Malformed);
+        }
+
+        let channel = |start: usize| u8::from_str_radix(&value[start + 1..start + 3], 16).unwrap();
+
+        Ok(HairColor {
+            red: channel(0),
+            green: channel(2),
+            blue: channel(4),
+        })
+    }
+}
+
+impl FromStr for EyeColor {
+    type Err = ValueError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if !is_eye_color(value) {
+            return Err(ValueError::OutOfRange);
+        }
+
+        EyeColor::ALL
+            .iter()
+            .copied()
+            .find(|color| color.code() == value)
+            .ok_or(ValueError::OutOfRange)
+    }
+}
+
+impl FromStr for PassportId {
+    type Err = ValueError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if is_passport_id(value) {
+            Ok(PassportId(value.to_owned()))
+        } else {
+            Err(ValueError::Malformed)
+        }
+    }
+}
+
+/// Parses a number made only of ASCII digits, so signs and spaces are rejected
+fn parse_number(value: &str) -> Result<u16, ValueError> {
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ValueError::Malformed);
+    }
+
+    // Digits that do not fit are still a number, just far too large
+    value.parse().map_err(|_| ValueError::OutOfRange)
+}
+
+/// Parses a four digit year that must fall within the range
+fn parse_year(value: &str, range: RangeInclusive<u16>) -> Result<u16, ValueError> {
+    if value.len() != 4 {
+        return Err(ValueError::Malformed);
+    }
+
+    let year = parse_number(value)?;
+    if range.contains(&year) {
+        Ok(year)
+    } else {
+        Err(ValueError::OutOfRange)
+    }
+}
+
+/// A passport whose required fields have all been validated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Passport {
+    pub birth_year: u16,
+    pub issue_year: u16,
+    pub expiration_year: u16,
+    pub height: Height,
+    pub hair_color: HairColor,
+    pub eye_color: EyeColor,
+    pub passport_id: PassportId,
+    /// The country id is optional and never checked
+    pub country_id: Option<String>,
+}
+
+/// Collects field errors while a passport is being built
+///
+/// Only the first value given for a key is used
+struct Builder<'a, K, V> {
+    fields: &'a [(K, V)],
+    errors: Vec<FieldError>,
+}
+
+impl<'a, K: AsRef<str>, V: AsRef<str>> Builder<'a, K, V> {
+    fn value(&self, field: Field) -> Option<&'a str> {
+        self.fields
+            .iter()
+            .find(|(key, _)| key.as_ref() == field.key())
+            .map(|(_, value)| value.as_ref())
+    }
+
+    /// Converts a required field, recording why it failed if it does
+    fn convert<T>(
+        &mut self,
+        field: Field,
+        parse: impl Fn(&str) -> Result<T, ValueError>,
+    ) -> Option<T> {
+        let value = match self.value(field) {
+            Some(value) => value,
+            None => {
+                self.errors.push(FieldError::Missing { field });
+                return None;
+            }
+        };
+
+        parse(value)
+            .map_err(|error| {
+                self.errors
+                    .push(FieldError::from_value_error(field, value, error))
+            })
+            .ok()
+    }
+}
+
+/// Builds a passport from owned or borrowed fields, such as those read by `stream`
+impl<K: AsRef<str>, V: AsRef<str>> TryFrom<&[(K, V)]> for Passport {
+    type Error = PassportError;
+
+    fn try_from(fields: &[(K, V)]) -> Result<Self, Self::Error> {
+        let mut builder = Builder {
+            fields,
+            errors: Vec::new(),
+        };
+
+        let birth_year = builder.convert(Field::BirthYear, |v| parse_year(v, BIRTH_YEARS));
+        let issue_year = builder.convert(Field::IssueYear, |v| parse_year(v, ISSUE_YEARS));
+        let expiration_year =
+            builder.convert(Field::ExpirationYear, |v| parse_year(v, EXPIRATION_YEARS));
+        let height = builder.convert(Field::Height, str::parse);
+        let hair_color = builder.convert(Field::HairColor, str::parse);
+        let eye_color = builder.convert(Field::EyeColor, str::parse);
+        let passport_id = builder.convert(Field::PassportId, str::parse);
+        let country_id = builder.value(Field::CountryId).map(str::to_owned);
+
+        match (
+            birth_year,
+            issue_year,
+            expiration_year,
+            height,
+            hair_color,
+            eye_color,
+            passport_id,
+        ) {
+            (
+                Some(birth_year),
+                Some(issue_year),
+                Some(expiration_year),
+                Some(height),
+                Some(hair_color),
+                Some(eye_color),
+                Some(passport_id),
+            ) => Ok(Passport {
+                birth_year,
+                issue_year,
+                expiration_year,
+                height,
+                hair_color,
+                eye_color,
+                passport_id,
+                country_id,
+            }),
+            _ => Err(PassportError {
+                errors: builder.errors,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fields;
+
+    #[test]
+    fn test_valid_passport() {
+        let fields = fields(
+            "eyr:2029 ecl:blu cid:129 byr:1989 iyr:2014 pid:096056539 hcl:#a97842 hgt:165cm",
+        );
+
+        assert_eq!(
+            Passport::try_from(fields.as_slice()),
+            Ok(Passport {
+                birth_year: 1989,
+                issue_year: 2014,
+                expiration_year: 2029,
+                height: Height::Centimeters(165),
+                hair_color: HairColor {
+                    red: 0xa9,
+                    green: 0x78,
+                    blue: 0x42
+                },
+                eye_color: EyeColor::Blue,
+                passport_id: "096056539".parse().unwrap(),
+                country_id: Some("129".to_owned()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_collects_every_error() {
+        let fields = fields("eyr:1972 cid:100 hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018");
+        let errors = Passport::try_from(fields.as_slice()).unwrap_err().errors;
+
+        assert_eq!(
+            errors,
+            vec![
+                FieldError::Missing {
+                    field: Field::BirthYear
+                },
+                FieldError::OutOfRange {
+                    field: Field::ExpirationYear,
+                    value: "1972".to_owned()
+                },
+                FieldError::Malformed {
+                    field: Field::Height,
+                    value: "170".to_owned()
+                },
+                FieldError::Malformed {
+                    field: Field::PassportId,
+                    value: "186cm".to_owned()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_height() {
+        assert_eq!("60in".parse(), Ok(Height::Inches(60)));
+        assert_eq!("193cm".parse(), Ok(Height::Centimeters(193)));
+        assert_eq!("190in".parse::<Height>(), Err(ValueError::OutOfRange));
+        assert_eq!("99999999cm".parse::<Height>(), Err(ValueError::OutOfRange));
+        assert_eq!("cm".parse::<Height>(), Err(ValueError::Malformed));
+        assert_eq!("+60in".parse::<Height>(), Err(ValueError::Malformed));
+        assert_eq!("190".parse::<Height>(), Err(ValueError::Malformed));
+    }
+
+    #[test]
+    fn test_hair_color() {
+        assert_eq!(
+            "#123abc".parse(),
+            Ok(HairColor {
+                red: 0x12,
+                green: 0x3a,
+                blue: 0xbc
+            })
+        );
+        assert_eq!("#123abz".parse::<HairColor>(), Err(ValueError::Malformed));
+        assert_eq!("#123ABC".parse::<HairColor>(), Err(ValueError::Malformed));
+        assert_eq!("123abc".parse::<HairColor>(), Err(ValueError::Malformed));
+        assert_eq!("#é3abc".parse::<HairColor>(), Err(ValueError::Malformed));
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for value in ["#00ff0a", "hzl", "000000001", "59in", "150cm"].iter() {
+            let displayed = match value.len() {
+                3 => value.parse::<EyeColor>().unwrap().to_string(),
+                7 => value.parse::<HairColor>().unwrap().to_string(),
+                9 => value.parse::<PassportId>().unwrap().to_string(),
+                _ => value.parse::<Height>().unwrap().to_string(),
+            };
+
+            assert_eq!(&displayed, value);
+        }
+    }
+
+    #[test]
+    fn test_years() {
+        assert_eq!(parse_year("2002", BIRTH_YEARS), Ok(2002));
+        assert_eq!(parse_year("2003", BIRTH_YEARS), Err(ValueError::OutOfRange));
+        assert_eq!(parse_year("02002", BIRTH_YEARS), Err(ValueError::Malformed));
+        assert_eq!(parse_year("+2002", BIRTH_YEARS), Err(ValueError::Malformed));
+        assert_eq!(parse_year("20o2", BIRTH_YEARS), Err(ValueError::Malformed));
+    }
+}
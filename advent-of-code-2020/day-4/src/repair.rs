@@ -0,0 +1,278 @@
+//! Suggests fixes for common mistakes in invalid passports
+//!
+//! Only values that fail validation are looked at, and a fix is only suggested
+//! when there is a single obvious correction that makes the value valid.
+//! Nothing changes until the suggestions are applied, which can be limited to
+//! those with enough confidence.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{check_field, is_passport_valid};
+
+/// How likely a suggestion is to be what was meant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl Confidence {
+    pub fn name(self) -> &'static str {
+        match self {
+            Confidence::Low => "low",
+            Confidence::Medium => "medium",
+            Confidence::High => "high",
+        }
+    }
+}
+
+impl FromStr for Confidence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "low" => Ok(Confidence::Low),
+            "medium" => Ok(Confidence::Medium),
+            "high" => Ok(Confidence::High),
+            other => Err(format!(
+                "unknown confidence '{}', expected low, medium or high",
+                other
+            )),
+        }
+    }
+}
+
+/// A proposed replacement for the value of one field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// The position of the passport in the batch, counting from 1
+    pub passport: usize,
+    pub key: String,
+    pub original: String,
+    pub replacement: String,
+    pub confidence: Confidence,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Passport {}: {} '{}' -> '{}' ({} confidence, {})",
+            self.passport,
+            self.key,
+            self.original,
+            self.replacement,
+            self.confidence.name(),
+            self.reason
+        )
+    }
+}
+
+fn is_hex(value: &str) -> bool {
+    value.len() == 6 && value.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Works out a fix for a single invalid value, if there is an obvious one
+fn repair_value(key: &str, value: &str) -> Option<(String, Confidence, &'static str)> {
+    match key {
+        "hcl" if is_hex(value) => Some((
+            format!("#{}", value.to_ascii_lowercase()),
+            Confidence::High,
+            "added the missing '#'",
+        )),
+        "hcl" if value.starts_with('#') && is_hex(&value[1..]) => Some((
+            value.to_ascii_lowercase(),
+            Confidence::High,
+            "lowercased the hex digits",
+        )),
+        "hgt" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+            // The two ranges do not overlap, so at most one unit can fit
+            let unit = ["cm", "in"]
+                .iter()
+                .find(|unit| check_field(key, &format!("{}{}", value, unit)).is_ok())?;
+
+            Some((
+                format!("{}{}", value, unit),
+                Confidence::Medium,
+                "inferred the unit from the height",
+            ))
+        }
+        "pid" if value.len() < 9 && value.bytes().all(|b| b.is_ascii_digit()) => {
+            let confidence = if value.len() == 8 {
+                Confidence::Medium
+            } else {
+                Confidence::Low
+            };
+
+            Some((
+                format!("{:0>9}", value),
+                confidence,
+                "padded with leading zeros",
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Suggests fixes for the invalid values of a passport, which is the `number`th in its batch
+pub fn suggest(fields: &[(String, String)], number: usize) -> Vec<Suggestion> {
+    fields
+        .iter()
+        .filter(|(key, value)| check_field(key, value).is_err())
+        .filter_map(|(key, value)| {
+            let (replacement, confidence, reason) = repair_value(key, value)?;
+
+            if check_field(key, &replacement).is_err() {
+                return None;
+            }
+
+            Some(Suggestion {
+                passport: number,
+                key: key.to_owned(),
+                original: value.to_owned(),
+                replacement,
+                confidence,
+                reason,
+            })
+        })
+        .collect()
+}
+
+/// Suggests fixes for every passport in the batch that fails strict validation
+pub fn suggest_batch(batch: &[Vec<(String, String)>]) -> Vec<Suggestion> {
+    batch
+        .iter()
+        .enumerate()
+        .filter(|(_, fields)| !is_passport_valid(fields, true))
+        .flat_map(|(index, fields)| suggest(fields, index + 1))
+        .collect()
+}
+
+/// Returns a copy of the batch with every suggestion of at least `minimum` confidence applied
+pub fn apply(
+    batch: &[Vec<(String, String)>],
+    suggestions: &[Suggestion],
+    minimum: Confidence,
+) -> Vec<Vec<(String, String)>> {
+    let mut repaired = batch.to_vec();
+
+    for suggestion in suggestions.iter().filter(|s| s.confidence >= minimum) {
+        let fields = &mut repaired[suggestion.passport - 1];
+
+        if let Some(field) = fields
+            .iter_mut()
+            .find(|(key, value)| *key == suggestion.key && *value == suggestion.original)
+        {
+            field.1 = suggestion.replacement.clone();
+        }
+    }
+
+    repaired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{count_valid_passports, parse_batch, write_batch, KeyPolicy, KeyRules};
+
+    fn batch() -> Vec<Vec<(String, String)>> {
+        parse_batch(
+            "byr:1980 iyr:2012 eyr:2030 hgt:74 hcl:623a2f ecl:grn pid:87499704\n\
+             \n\
+             byr:1989 iyr:2014 eyr:2029 hgt:165 hcl:#A97842 ecl:blu pid:96056539\n\
+             \n\
+             byr:1989 iyr:2014 eyr:2029 hgt:100 hcl:#a97842z ecl:blu pid:1234\n\
+             \n\
+             byr:1944 iyr:2010 eyr:2021 hgt:158cm hcl:b6652a ecl:blu pid:093154719\n",
+        )
+        .unwrap()
+    }
+
+    fn repairs(suggestions: &[Suggestion]) -> Vec<(usize, &str, &str, Confidence)> {
+        suggestions
+            .iter()
+            .map(|s| {
+                (
+                    s.passport,
+                    s.key.as_str(),
+                    s.replacement.as_str(),
+                    s.confidence,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let suggestions = suggest_batch(&batch());
+
+        assert_eq!(
+            repairs(&suggestions),
+            vec![
+                (1, "hgt", "74in", Confidence::Medium),
+                (1, "hcl", "#623a2f", Confidence::High),
+                (1, "pid", "087499704", Confidence::Medium),
+                (2, "hgt", "165cm", Confidence::Medium),
+                (2, "hcl", "#a97842", Confidence::High),
+                (2, "pid", "096056539", Confidence::Medium),
+                (3, "pid", "000001234", Confidence::Low),
+                (4, "hcl", "#b6652a", Confidence::High),
+            ]
+        );
+        assert_eq!(
+            suggestions[1].to_string(),
+            "Passport 1: hcl '623a2f' -> '#623a2f' (high confidence, added the missing '#')"
+        );
+    }
+
+    #[test]
+    fn test_apply_by_confidence() {
+        let batch = batch();
+        let suggestions = suggest_batch(&batch);
+
+        let repaired = apply(&batch, &suggestions, Confidence::Medium);
+        let valid: Vec<bool> = repaired
+            .iter()
+            .map(|fields| is_passport_valid(fields, true))
+            .collect();
+        assert_eq!(valid, vec![true, true, false, true]);
+
+        let repaired = apply(&batch, &suggestions, Confidence::High);
+        assert_eq!(repaired[0][4].1, "#623a2f");
+        assert_eq!(repaired[0][3].1, "74");
+        assert!(is_passport_valid(&repaired[3], true));
+    }
+
+    #[test]
+    fn test_apply_only_replaces_suggested_values() {
+        let batch = parse_batch(
+            "pid:087499704 byr:1980 hgt:74 byr:1850 iyr:2012 eyr:2030 hcl:#623a2f ecl:grn\n",
+        )
+        .unwrap();
+        let repaired = apply(&batch, &suggest_batch(&batch), Confidence::Low);
+
+        assert_eq!(
+            write_batch(&repaired),
+            "pid:087499704 byr:1980 hgt:74in byr:1850 iyr:2012 eyr:2030 hcl:#623a2f ecl:grn\n"
+        );
+
+        let strict = KeyRules {
+            duplicate: KeyPolicy::Strict,
+            ..KeyRules::default()
+        };
+        assert_eq!(count_valid_passports(&repaired, true, strict), 0);
+    }
+
+    #[test]
+    fn test_valid_passports_are_left_alone() {
+        let batch =
+            parse_batch("byr:1944 iyr:2010 eyr:2021 hgt:158cm hcl:#b6652a ecl:blu pid:093154719\n")
+                .unwrap();
+
+        assert!(suggest_batch(&batch).is_empty());
+        assert_eq!(apply(&batch, &[], Confidence::Low), batch);
+    }
+}
@@ -0,0 +1,457 @@
+//! Explains why each passport in a batch passed or failed validation
+//!
+//! A report lists the violations of every passport and tallies them across the
+//! batch by field and the check that failed, such as `37 failed on hgt units`.
+//! Passports are judged by the same rules as part two unless a schema is given.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt::{self, Write};
+
+use serde_json::json;
+
+use crate::passport::{Field, FieldError, Passport};
+#[cfg(feature = "schema")]
+use crate::schema::Schema;
+
+/// The part of a rule that a value failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Check {
+    /// A required field was not given
+    Presence,
+    Pattern,
+    /// A number or measurement contained something other than digits
+    Type,
+    /// A measurement had no unit, or one the rule does not list
+    Units,
+    Range,
+    Values,
+}
+
+impl Check {
+    pub fn name(self) -> &'static str {
+        match self {
+            Check::Presence => "presence",
+            Check::Pattern => "pattern",
+            Check::Type => "type",
+            Check::Units => "units",
+            Check::Range => "range",
+            Check::Values => "values",
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Describes why a single field of a passport breaks the schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    Missing {
+        key: String,
+    },
+    /// The value does not match the field's type, unit or pattern
+    Malformed {
+        key: String,
+        value: String,
+        check: Check,
+    },
+    /// The value is outside the field's range or list of values
+    OutOfRange {
+        key: String,
+        value: String,
+        check: Check,
+    },
+}
+
+impl Violation {
+    pub fn key(&self) -> &str {
+        match self {
+            Violation::Missing { key }
+            | Violation::Malformed { key, .. }
+            | Violation::OutOfRange { key, .. } => key,
+        }
+    }
+
+    /// The value that broke the rule, if the field was given at all
+    pub fn value(&self) -> Option<&str> {
+        match self {
+            Violation::Missing { .. } => None,
+            Violation::Malformed { value, .. } | Violation::OutOfRange { value, .. } => Some(value),
+        }
+    }
+
+    pub fn check(&self) -> Check {
+        match self {
+            Violation::Missing { .. } => Check::Presence,
+            Violation::Malformed { check, .. } | Violation::OutOfRange { check, .. } => *check,
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::Missing { key } => write!(f, "{} is missing", key),
+            Violation::Malformed { key, value, check } => {
+                write!(f, "{} '{}' is malformed ({})", key, value, check)
+            }
+            Violation::OutOfRange { key, value, check } => {
+                write!(f, "{} '{}' is out of range ({})", key, value, check)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Violation {}
+
+/// The violations found in a single passport
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassportReport {
+    /// The position of the passport in the batch, counting from 1
+    pub number: usize,
+    /// At most one violation per field, in order of key as a schema lists them
+    pub violations: Vec<Violation>,
+}
+
+impl PassportReport {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// The result of checking every passport in a batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchReport {
+    pub passports: Vec<PassportReport>,
+}
+
+/// Names the check a malformed height fails, splitting it like a schema's measurement
+///
+/// The unit is everything after the leading digits, so only a value with no
+/// digits before `cm` or `in` has the wrong type; anything else has a bad unit.
+fn height_check(value: &str) -> Check {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+
+    match &value[split..] {
+        "cm" | "in" => Check::Type,
+        _ => Check::Units,
+    }
+}
+
+/// Describes a field error as a violation, naming the check a schema would make
+fn violation(error: FieldError) -> Violation {
+    let check = match &error {
+        FieldError::Missing { .. } => Check::Presence,
+        FieldError::OutOfRange { field, .. } if *field == Field::EyeColor => Check::Values,
+        FieldError::OutOfRange { .. } => Check::Range,
+        FieldError::Malformed { field, value } if *field == Field::Height => height_check(value),
+        FieldError::Malformed { .. } => Check::Pattern,
+    };
+    let key = error.field().key().to_owned();
+
+    match error {
+        FieldError::Missing { .. } => Violation::Missing { key },
+        FieldError::Malformed { value, .. } => Violation::Malformed { key, value, check },
+        FieldError::OutOfRange { value, .. } => Violation::OutOfRange { key, value, check },
+    }
+}
+
+/// Checks a single passport, which is the `number`th in its batch, by the puzzle's rules
+pub fn diagnose(fields: &[(String, String)], number: usize) -> PassportReport {
+    let mut violations: Vec<Violation> = match Passport::try_from(fields) {
+        Ok(_) => Vec::new(),
+        Err(error) => error.errors.into_iter().map(violation).collect(),
+    };
+    violations.sort_by(|a, b| a.key().cmp(b.key()));
+
+    PassportReport { number, violations }
+}
+
+/// Checks every passport in a batch by the puzzle's rules
+pub fn diagnose_batch(batch: &[Vec<(String, String)>]) -> BatchReport {
+    BatchReport {
+        passports: batch
+            .iter()
+            .enumerate()
+            .map(|(index, fields)| diagnose(fields, index + 1))
+            .collect(),
+    }
+}
+
+/// Checks a single passport, which is the `number`th in its batch, against a schema
+#[cfg(feature = "schema")]
+pub fn diagnose_with_schema(
+    fields: &[(String, String)],
+    schema: &Schema,
+    number: usize,
+) -> PassportReport {
+    PassportReport {
+        number,
+        violations: schema.violations(fields),
+    }
+}
+
+/// Checks every passport in a batch against a schema
+#[cfg(feature = "schema")]
+pub fn diagnose_batch_with_schema(batch: &[Vec<(String, String)>], schema: &Schema) -> BatchReport {
+    BatchReport {
+        passports: batch
+            .iter()
+            .enumerate()
+            .map(|(index, fields)| diagnose_with_schema(fields, schema, index + 1))
+            .collect(),
+    }
+}
+
+impl BatchReport {
+    pub fn valid(&self) -> usize {
+        self.passports.iter().filter(|p| p.is_valid()).count()
+    }
+
+    pub fn invalid(&self) -> usize {
+        self.passports.len() - self.valid()
+    }
+
+    /// Counts the violations of each check on each field, most common first
+    pub fn failures(&self) -> Vec<((String, Check), usize)> {
+        let mut counts: BTreeMap<(String, Check), usize> = BTreeMap::new();
+
+        for violation in self.passports.iter().flat_map(|p| &p.violations) {
+            *counts
+                .entry((violation.key().to_owned(), violation.check()))
+                .or_insert(0) += 1;
+        }
+
+        let mut failures: Vec<_> = counts.into_iter().collect();
+        failures.sort_by_key(|&(_, count)| Reverse(count));
+        failures
+    }
+
+    /// Renders the report as text, listing each invalid passport if `detailed` is set
+    pub fn to_text(&self, detailed: bool) -> String {
+        let mut output = String::new();
+
+        if detailed {
+            for passport in self.passports.iter().filter(|p| !p.is_valid()) {
+                let violations: Vec<String> =
+                    passport.violations.iter().map(|v| v.to_string()).collect();
+
+                writeln!(
+                    output,
+                    "Passport {}: {}",
+                    passport.number,
+                    violations.join(", ")
+                )
+                .unwrap();
+            }
+        }
+
+        writeln!(
+            output,
+            "{} passports, {} valid, {} invalid",
+            self.passports.len(),
+            self.valid(),
+            self.invalid()
+        )
+        .unwrap();
+
+        for ((key, check), count) in self.failures() {
+            writeln!(output, "{} failed on {} {}", count, key, check).unwrap();
+        }
+
+        output
+    }
+
+    /// Renders the report as a JSON document
+    pub fn to_json(&self) -> String {
+        let failures: Vec<_> = self
+            .failures()
+            .into_iter()
+            .map(|((key, check), count)| {
+                json!({ "field": key, "check": check.name(), "count": count })
+            })
+            .collect();
+
+        let invalid: Vec<_> = self
+            .passports
+            .iter()
+            .filter(|p| !p.is_valid())
+            .map(|passport| {
+                let violations: Vec<_> = passport
+                    .violations
+                    .iter()
+                    .map(|violation| {
+                        json!({
+                            "field": violation.key(),
+                            "check": violation.check().name(),
+                            "value": violation.value(),
+                            "message": violation.to_string(),
+                        })
+                    })
+                    .collect();
+
+                json!({ "passport": passport.number, "violations": violations })
+            })
+            .collect();
+
+        let report = json!({
+            "passports": self.passports.len(),
+            "valid": self.valid(),
+            "invalid": self.invalid(),
+            "failures": failures,
+            "invalid_passports": invalid,
+        });
+
+        serde_json::to_string_pretty(&report).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{self, DefectRates};
+    use crate::{parse_batch, puzzle_two_solution};
+
+    fn batch() -> Vec<Vec<(String, String)>> {
+        parse_batch(
+            "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd byr:1937 iyr:2017 cid:147 hgt:183cm\n\
+             \n\
+             iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884 hcl:#cfa07d byr:1929\n\
+             \n\
+             hcl:#ae17e1 iyr:2013 eyr:2024 ecl:brn pid:760753108 byr:1931 hgt:179\n\
+             \n\
+             hcl:#cfa07d eyr:2025 pid:166559648 iyr:2011 ecl:brn hgt:59\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_diagnose_batch() {
+        let report = diagnose_batch(&batch());
+
+        assert_eq!(report.valid(), 1);
+        assert_eq!(report.invalid(), 3);
+        assert!(report.passports[0].is_valid());
+        assert_eq!(report.passports[3].number, 4);
+        assert_eq!(
+            report.failures(),
+            vec![
+                (("hgt".to_owned(), Check::Units), 2),
+                (("byr".to_owned(), Check::Presence), 1),
+                (("hgt".to_owned(), Check::Presence), 1),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "schema")]
+    fn test_schema_report() {
+        let schema = Schema::from_toml("[fields.hgt]\nrequired = false").unwrap();
+        let report = diagnose_batch_with_schema(&batch(), &schema);
+
+        // Only the height is checked, and it may be left out
+        assert_eq!(report.valid(), 4);
+        assert_eq!(
+            diagnose_batch_with_schema(&batch(), &Schema::default()),
+            diagnose_batch(&batch())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "schema")]
+    fn test_default_schema_report_matches_built_in_report() {
+        let schema = Schema::default();
+
+        for seed in 0..5 {
+            let batch = generator::generate(seed, 2_000, &DefectRates::uniform(0.3)).batch();
+
+            assert_eq!(
+                diagnose_batch_with_schema(&batch, &schema),
+                diagnose_batch(&batch)
+            );
+        }
+
+        let batch = parse_batch(
+            "hgt:+74in ecl:zzz\n\nhgt:1e2cm byr:19800\n\nhgt:cm pid:+87499704\n\n\
+             hgt:74 hcl:#623a2f0\n\nhgt:99999999999999999999cm iyr:0000\n",
+        )
+        .unwrap();
+        assert_eq!(
+            diagnose_batch_with_schema(&batch, &schema),
+            diagnose_batch(&batch)
+        );
+    }
+
+    #[test]
+    fn test_report_matches_puzzle_two() {
+        for seed in 0..5 {
+            let batch = generator::generate(seed, 2_000, &DefectRates::uniform(0.3)).batch();
+            let report = diagnose_batch(&batch);
+
+            assert_eq!(report.valid() as i32, puzzle_two_solution(&batch));
+            assert!(report.invalid() > 0);
+        }
+
+        let batch = parse_batch("ecl:zzz hgt:+74in\n").unwrap();
+        let checks: Vec<Check> = diagnose_batch(&batch).passports[0]
+            .violations
+            .iter()
+            .map(|v| v.check())
+            .collect();
+        assert_eq!(
+            checks,
+            vec![
+                Check::Presence,
+                Check::Values,
+                Check::Presence,
+                Check::Presence,
+                Check::Units,
+                Check::Presence,
+                Check::Presence,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_text_report() {
+        let report = diagnose_batch(&batch());
+
+        assert_eq!(
+            report.to_text(false),
+            "4 passports, 1 valid, 3 invalid\n\
+             2 failed on hgt units\n\
+             1 failed on byr presence\n\
+             1 failed on hgt presence\n"
+        );
+        assert!(report.to_text(true).starts_with(
+            "Passport 2: hgt is missing\nPassport 3: hgt '179' is malformed (units)\n"
+        ));
+    }
+
+    #[test]
+    fn test_json_report() {
+        let report = diagnose_batch(&batch());
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+
+        assert_eq!(json["valid"], 1);
+        assert_eq!(json["failures"][0]["field"], "hgt");
+        assert_eq!(json["failures"][0]["check"], "units");
+        assert_eq!(json["failures"][0]["count"], 2);
+        assert_eq!(json["invalid_passports"][0]["passport"], 2);
+        assert_eq!(
+            json["invalid_passports"][0]["violations"][0]["value"],
+            serde_json::Value::Null
+        );
+        assert_eq!(
+            json["invalid_passports"][1]["violations"][0]["value"],
+            "179"
+        );
+    }
+}
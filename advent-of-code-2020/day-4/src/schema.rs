@@ -0,0 +1,546 @@
+//! Passport rules loaded from a TOML or JSON schema
+//!
+//! A schema lists each field by key along with what its value must look like,
+//! so passports from other countries can be checked without changing the code.
+//! The puzzle's own rules are shipped as `schemas/default.toml`, which also
+//! documents every setting a field can have.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use regex::Regex;
+use serde::Deserialize;
+
+pub use crate::report::{Check, Violation};
+
+/// The text of the schema describing the puzzle's rules
+pub const DEFAULT_SCHEMA: &str = include_str!("../schemas/default.toml");
+
+/// Describes why a schema could not be loaded
+#[derive(Debug)]
+pub enum SchemaError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    Json(serde_json::Error),
+    /// The file extension was neither `.toml` nor `.json`
+    UnknownFormat(String),
+    InvalidPattern {
+        key: String,
+        error: regex::Error,
+    },
+    /// The settings of a field contradict each other
+    InvalidRule {
+        key: String,
+        reason: &'static str,
+    },
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemaError::Io(e) => write!(f, "{}", e),
+            SchemaError::Toml(e) => write!(f, "invalid TOML schema: {}", e),
+            SchemaError::Json(e) => write!(f, "invalid JSON schema: {}", e),
+            SchemaError::UnknownFormat(path) => {
+                write!(f, "'{}' should end in .toml or .json", path)
+            }
+            SchemaError::InvalidPattern { key, error } => {
+                write!(f, "invalid pattern for {}: {}", key, error)
+            }
+            SchemaError::InvalidRule { key, reason } => write!(f, "{} {}", key, reason),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// The kind of value a field holds
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueType {
+    #[default]
+    Text,
+    /// A whole number made only of digits
+    Number,
+    /// A whole number followed by a unit, such as `183cm`
+    Measurement,
+}
+
+/// An inclusive range where either end may be left open
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Range {
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+}
+
+impl Range {
+    pub fn contains(&self, value: u64) -> bool {
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+    }
+}
+
+/// A field as it is written in a schema file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FieldDefinition {
+    #[serde(default = "required_by_default")]
+    required: bool,
+    #[serde(rename = "type", default)]
+    value_type: ValueType,
+    min: Option<u64>,
+    max: Option<u64>,
+    #[serde(default)]
+    units: BTreeMap<String, Range>,
+    pattern: Option<String>,
+    values: Option<Vec<String>>,
+}
+
+fn required_by_default() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SchemaDefinition {
+    fields: BTreeMap<String, FieldDefinition>,
+}
+
+/// The checks made against a single field
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub required: bool,
+    pub value_type: ValueType,
+    /// The range of a number, unused by other types
+    pub range: Range,
+    /// The range allowed for each unit of a measurement
+    pub units: BTreeMap<String, Range>,
+    pub pattern: Option<Regex>,
+    pub values: Option<Vec<String>>,
+}
+
+impl Rule {
+    fn from_definition(key: &str, definition: FieldDefinition) -> Result<Self, SchemaError> {
+        let invalid = |reason| SchemaError::InvalidRule {
+            key: key.to_owned(),
+            reason,
+        };
+        let has_range = definition.min.is_some() || definition.max.is_some();
+
+        match definition.value_type {
+            ValueType::Measurement if definition.units.is_empty() => {
+                return Err(invalid("is a measurement without any units"))
+            }
+            ValueType::Measurement if has_range => {
+                return Err(invalid(
+                    "should give a range for each unit instead of min or max",
+                ))
+            }
+            ValueType::Text if has_range => return Err(invalid("is text so cannot have a range")),
+            ValueType::Text | ValueType::Number if !definition.units.is_empty() => {
+                return Err(invalid("has units but is not a measurement"))
+            }
+            _ => {}
+        }
+
+        let pattern = definition
+            .pattern
+            .map(|pattern| Regex::new(&pattern))
+            .transpose()
+            .map_err(|error| SchemaError::InvalidPattern {
+                key: key.to_owned(),
+                error,
+            })?;
+
+        Ok(Rule {
+            required: definition.required,
+            value_type: definition.value_type,
+            range: Range {
+                min: definition.min,
+                max: definition.max,
+            },
+            units: definition.units,
+            pattern,
+            values: definition.values,
+        })
+    }
+
+    /// Checks a value against the rule, returning a violation for `key` if it fails
+    pub fn check(&self, key: &str, value: &str) -> Option<Violation> {
+        let malformed = |check| {
+            Some(Violation::Malformed {
+                key: key.to_owned(),
+                value: value.to_owned(),
+                check,
+            })
+        };
+        let out_of_range = |check| {
+            Some(Violation::OutOfRange {
+                key: key.to_owned(),
+                value: value.to_owned(),
+                check,
+            })
+        };
+
+        if let Some(pattern) = &self.pattern {
+            if !pattern.is_match(value) {
+                return malformed(Check::Pattern);
+            }
+        }
+
+        let (number, range) = match self.value_type {
+            ValueType::Text => (None, self.range),
+            ValueType::Number => (Some(value), self.range),
+            ValueType::Measurement => {
+                let split = value
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(value.len());
+
+                match self.units.get(&value[split..]) {
+                    Some(range) => (Some(&value[..split]), *range),
+                    None => return malformed(Check::Units),
+                }
+            }
+        };
+
+        if let Some(number) = number {
+            if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+                return malformed(Check::Type);
+            }
+
+            // Digits that do not fit are still a number, just far too large
+            match number.parse() {
+                Ok(number) if range.contains(number) => {}
+                _ => return out_of_range(Check::Range),
+            }
+        }
+
+        if let Some(values) = &self.values {
+            if !values.iter().any(|allowed| allowed == value) {
+                return out_of_range(Check::Values);
+            }
+        }
+
+        None
+    }
+}
+
+/// A set of rules for the fields of a passport
+#[derive(Debug, Clone)]
+pub struct Schema {
+    rules: BTreeMap<String, Rule>,
+}
+
+impl Default for Schema {
+    /// The puzzle's rules, loaded from `DEFAULT_SCHEMA`
+    fn default() -> Self {
+        Schema::from_toml(DEFAULT_SCHEMA).unwrap()
+    }
+}
+
+impl Schema {
+    fn from_definition(definition: SchemaDefinition) -> Result<Self, SchemaError> {
+        let rules = definition
+            .fields
+            .into_iter()
+            .map(|(key, field)| Rule::from_definition(&key, field).map(|rule| (key, rule)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Schema { rules })
+    }
+
+    pub fn from_toml(input: &str) -> Result<Self, SchemaError> {
+        Schema::from_definition(toml::from_str(input).map_err(SchemaError::Toml)?)
+    }
+
+    pub fn from_json(input: &str) -> Result<Self, SchemaError> {
+        Schema::from_definition(serde_json::from_str(input).map_err(SchemaError::Json)?)
+    }
+
+    /// Loads a schema file, choosing the format from its extension
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SchemaError> {
+        let path = path.as_ref();
+        let input = fs::read_to_string(path).map_err(SchemaError::Io)?;
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Schema::from_toml(&input),
+            Some("json") => Schema::from_json(&input),
+            _ => Err(SchemaError::UnknownFormat(path.display().to_string())),
+        }
+    }
+
+    pub fn rule(&self, key: &str) -> Option<&Rule> {
+        self.rules.get(key)
+    }
+
+    /// Iterates over every rule in order of its key
+    pub fn rules(&self) -> impl Iterator<Item = (&str, &Rule)> {
+        self.rules.iter().map(|(key, rule)| (key.as_str(), rule))
+    }
+
+    /// Finds the first value given for a key
+    fn value<'a>(fields: &'a [(String, String)], key: &str) -> Option<&'a str> {
+        fields
+            .iter()
+            .find(|(field, _)| field == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Lists every required field that is missing
+    pub fn missing_fields(&self, fields: &[(String, String)]) -> Vec<Violation> {
+        self.rules()
+            .filter(|(key, rule)| rule.required && Schema::value(fields, key).is_none())
+            .map(|(key, _)| Violation::Missing {
+                key: key.to_owned(),
+            })
+            .collect()
+    }
+
+    /// Lists every way the fields break the schema, in order of key
+    ///
+    /// Keys the schema does not mention are ignored
+    pub fn violations(&self, fields: &[(String, String)]) -> Vec<Violation> {
+        self.rules()
+            .filter_map(|(key, rule)| match Schema::value(fields, key) {
+                Some(value) => rule.check(key, value),
+                None if rule.required => Some(Violation::Missing {
+                    key: key.to_owned(),
+                }),
+                None => None,
+            })
+            .collect()
+    }
+
+    pub fn has_required_fields(&self, fields: &[(String, String)]) -> bool {
+        self.missing_fields(fields).is_empty()
+    }
+
+    pub fn is_valid(&self, fields: &[(String, String)]) -> bool {
+        self.violations(fields).is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{self, DefectRates};
+    use crate::passport::Field;
+    use crate::tests::fields;
+    use crate::{count_valid_with_schema, is_passport_valid};
+
+    /// Checks that the default schema and the built-in rules agree on a passport
+    fn assert_same_as_puzzle_rules(schema: &Schema, fields: &[(String, String)]) {
+        let fields = fields.to_vec();
+
+        assert_eq!(
+            schema.has_required_fields(&fields),
+            is_passport_valid(&fields, false),
+            "{:?}",
+            fields
+        );
+        assert_eq!(
+            schema.is_valid(&fields),
+            is_passport_valid(&fields, true),
+            "{:?}",
+            fields
+        );
+    }
+
+    #[test]
+    fn test_default_schema_matches_puzzle_rules_on_edge_values() {
+        let schema = Schema::default();
+        let valid = fields("pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f");
+        let values = [
+            "",
+            "0",
+            "1920",
+            "2002",
+            "2010",
+            "2020",
+            "2030",
+            "1919",
+            "2031",
+            "+1980",
+            "-1980",
+            "01980",
+            "19800",
+            "198O",
+            " 1980",
+            "٣٣٣٣",
+            "150cm",
+            "193cm",
+            "59in",
+            "76in",
+            "149cm",
+            "77in",
+            "074in",
+            "+74in",
+            "-74in",
+            "74",
+            "74IN",
+            "in",
+            "cm",
+            "0cm",
+            "99999999999999999999cm",
+            "1e2cm",
+            "74in ",
+            "#623a2f",
+            "#623A2F",
+            "623a2f",
+            "#623a2",
+            "#623a2f0",
+            "#62 a2f",
+            "amb",
+            "oth",
+            "AMB",
+            "xyz",
+            "ambb",
+            "087499704",
+            "000000000",
+            "87499704",
+            "0087499704",
+            "+87499704",
+            "08749970٣",
+        ];
+
+        for field in Field::REQUIRED.iter().map(|field| field.key()) {
+            for value in values.iter() {
+                let mut replaced = valid.clone();
+                for pair in replaced.iter_mut().filter(|(key, _)| key == field) {
+                    pair.1 = value.to_string();
+                }
+                assert_same_as_puzzle_rules(&schema, &replaced);
+
+                // Only the first value of a repeated key counts
+                let mut repeated = valid.clone();
+                repeated.push((field.to_owned(), value.to_string()));
+                assert_same_as_puzzle_rules(&schema, &repeated);
+            }
+
+            let mut missing = valid.clone();
+            missing.retain(|(key, _)| key != field);
+            assert_same_as_puzzle_rules(&schema, &missing);
+        }
+    }
+
+    #[test]
+    fn test_default_schema_matches_puzzle_rules_on_generated_batches() {
+        let schema = Schema::default();
+
+        for seed in 0..10 {
+            let generated = generator::generate(seed, 1_000, &DefectRates::uniform(0.3));
+
+            for passport in &generated.passports {
+                assert_same_as_puzzle_rules(&schema, &passport.fields);
+            }
+            assert_eq!(
+                count_valid_with_schema(&generated.batch(), &schema, true),
+                generated.expected_valid() as usize
+            );
+        }
+    }
+
+    #[test]
+    fn test_default_schema_violations() {
+        let schema = Schema::default();
+        let fields = fields("eyr:1972 cid:100 hcl:#18171d ecl:zzz hgt:170 pid:186cm iyr:+2018");
+
+        assert_eq!(
+            schema
+                .violations(&fields)
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "byr is missing",
+                "ecl 'zzz' is out of range (values)",
+                "eyr '1972' is out of range (range)",
+                "hgt '170' is malformed (units)",
+                "iyr '+2018' is malformed (pattern)",
+                "pid '186cm' is malformed (pattern)",
+            ]
+        );
+        assert_eq!(
+            schema.missing_fields(&fields),
+            vec![Violation::Missing {
+                key: "byr".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_measurements() {
+        let rule = Schema::default().rule("hgt").unwrap().clone();
+
+        assert_eq!(rule.check("hgt", "76in"), None);
+        assert_eq!(rule.check("hgt", "150cm"), None);
+        assert!(matches!(
+            rule.check("hgt", "77in"),
+            Some(Violation::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            rule.check("hgt", "99999999999999999999999cm"),
+            Some(Violation::OutOfRange { .. })
+        ));
+        assert_eq!(rule.check("hgt", "cm").unwrap().check(), Check::Type);
+        for value in ["60ft", "60", "6o0in", "-60in"].iter() {
+            assert_eq!(
+                rule.check("hgt", value).unwrap().check(),
+                Check::Units,
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_json_schema_for_another_country() {
+        let schema = Schema::from_json(
+            r#"{
+                "fields": {
+                    "age": { "type": "number", "min": 18 },
+                    "wgt": { "type": "measurement", "units": { "kg": { "max": 200 } } },
+                    "nat": { "values": ["elf", "reindeer"], "required": false }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert!(schema.is_valid(&fields("age:30 wgt:80kg nat:elf")));
+        assert!(schema.is_valid(&fields("wgt:0kg age:18")));
+        assert!(!schema.is_valid(&fields("age:17 wgt:80kg")));
+        assert!(!schema.is_valid(&fields("age:30 wgt:80kg nat:human")));
+        assert!(!schema.has_required_fields(&fields("age:30 nat:elf")));
+    }
+
+    #[test]
+    fn test_schema_errors() {
+        assert!(matches!(
+            Schema::from_toml("[fields.hcl]\npattern = \"[\""),
+            Err(SchemaError::InvalidPattern { .. })
+        ));
+        assert!(matches!(
+            Schema::from_toml("[fields.hgt]\ntype = \"measurement\""),
+            Err(SchemaError::InvalidRule { .. })
+        ));
+        assert!(matches!(
+            Schema::from_toml("[fields.hcl]\nmin = 3"),
+            Err(SchemaError::InvalidRule { .. })
+        ));
+        assert!(matches!(
+            Schema::from_toml("[fields.hcl]\nrequird = false"),
+            Err(SchemaError::Toml(_))
+        ));
+        assert!(matches!(
+            Schema::from_json(r#"{"fields": {"byr": {"type": "date"}}}"#),
+            Err(SchemaError::Json(_))
+        ));
+        assert!(matches!(
+            Schema::load("README.md"),
+            Err(SchemaError::UnknownFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,254 @@
+//! Reads passports one at a time from a batch file of any size
+//!
+//! `parse_batch` holds the whole file and every field in memory at once. A
+//! `BatchReader` instead keeps only the passport being read, and hands out its
+//! fields as `&str` slices of an internal buffer that is reused for the next
+//! passport. Records follow the same rules as `common::records`: lines are
+//! trimmed, and blank or whitespace-only lines separate passports. Lines are
+//! read as bytes, so a line that is not UTF-8 is reported with its number.
+
+use std::fmt;
+use std::io::{self, BufRead};
+use std::str;
+
+use common::records::RecordError;
+
+use crate::{is_passport_valid_with, KeyRules};
+
+/// Describes why a batch could not be read
+#[derive(Debug)]
+pub enum StreamError {
+    Io(io::Error),
+    Record(RecordError),
+    /// A line was not valid UTF-8
+    InvalidUtf8 {
+        line: usize,
+    },
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StreamError::Io(e) => write!(f, "{}", e),
+            StreamError::Record(e) => write!(f, "{}", e),
+            StreamError::InvalidUtf8 { line } => write!(f, "line {} is not valid UTF-8", line),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+/// Reads the passports of a batch one at a time
+#[derive(Debug)]
+pub struct BatchReader<R> {
+    reader: R,
+    line: Vec<u8>,
+    record: String,
+    line_number: usize,
+}
+
+impl<R: BufRead> BatchReader<R> {
+    pub fn new(reader: R) -> Self {
+        BatchReader {
+            reader,
+            line: Vec::new(),
+            record: String::new(),
+            line_number: 0,
+        }
+    }
+
+    /// Reads the next passport, or `None` at the end of the batch
+    ///
+    /// The fields borrow from the reader, so they must be dropped before the
+    /// next passport is read.
+    pub fn next_passport(&mut self) -> Result<Option<Vec<(&str, &str)>>, StreamError> {
+        self.record.clear();
+
+        loop {
+            self.line.clear();
+            let read = self
+                .reader
+                .read_until(b'\n', &mut self.line)
+                .map_err(StreamError::Io)?;
+            if read == 0 {
+                break;
+            }
+            self.line_number += 1;
+
+            let line = str::from_utf8(&self.line)
+                .map_err(|_| StreamError::InvalidUtf8 {
+                    line: self.line_number,
+                })?
+                .trim();
+            if line.is_empty() {
+                if self.record.is_empty() {
+                    continue;
+                }
+                break;
+            }
+
+            if let Some(field) = line.split_whitespace().find(|field| !field.contains(':')) {
+                return Err(StreamError::Record(RecordError::MissingSeparator {
+                    line: self.line_number,
+                    field: field.to_owned(),
+                }));
+            }
+
+            self.record.push_str(line);
+            self.record.push('\n');
+        }
+
+        if self.record.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            self.record
+                .split_whitespace()
+                .filter_map(|field| field.split_once(':'))
+                .collect(),
+        ))
+    }
+}
+
+/// Counts the valid passports in a batch without holding more than one in memory
+pub fn count_valid<R: BufRead>(
+    reader: R,
+    strict: bool,
+    rules: KeyRules,
+) -> Result<usize, StreamError> {
+    let mut reader = BatchReader::new(reader);
+    let mut valid_count = 0;
+
+    while let Some(fields) = reader.next_passport()? {
+        if is_passport_valid_with(&fields, strict, rules) {
+            valid_count += 1;
+        }
+    }
+
+    Ok(valid_count)
+}
+
+/// Counts the passports that pass part one and part two, reading the batch once
+pub fn count_valid_parts<R: BufRead>(
+    reader: R,
+    rules: KeyRules,
+) -> Result<(usize, usize), StreamError> {
+    let mut reader = BatchReader::new(reader);
+    let mut counts = (0, 0);
+
+    while let Some(fields) = reader.next_passport()? {
+        if is_passport_valid_with(&fields, false, rules) {
+            counts.0 += 1;
+
+            if is_passport_valid_with(&fields, true, rules) {
+                counts.1 += 1;
+            }
+        }
+    }
+
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    use crate::{count_valid_passports, parse_batch};
+
+    const BATCH: &str = "\
+        ecl:gry pid:860033327 eyr:2020 hcl:#fffffd\r\n\
+        byr:1937 iyr:2017 cid:147 hgt:183cm\r\n\
+        \r\n\
+        iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884\n\
+        hcl:#cfa07d byr:1929\n\
+        \t \n\
+        \n\
+        hcl:#ae17e1 iyr:2013\n  eyr:2024\n\
+        ecl:brn pid:760753108 byr:1931\n\
+        hgt:179cm\n\
+        \n\
+        hcl:#cfa07d eyr:2025 pid:166559648\n\
+        iyr:2011 ecl:brn hgt:59in\n\n";
+
+    #[test]
+    fn test_matches_parse_batch() {
+        let batch = parse_batch(BATCH).unwrap();
+        // A tiny buffer makes lines span several reads
+        let mut reader = BatchReader::new(BufReader::with_capacity(4, BATCH.as_bytes()));
+        let mut streamed = Vec::new();
+
+        while let Some(fields) = reader.next_passport().unwrap() {
+            let fields: Vec<(String, String)> = fields
+                .into_iter()
+                .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                .collect();
+            streamed.push(fields);
+        }
+
+        assert_eq!(streamed, batch);
+        assert!(reader.next_passport().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_count_valid() {
+        let batch = parse_batch(BATCH).unwrap();
+
+        for &strict in &[false, true] {
+            let rules = KeyRules::default();
+
+            assert_eq!(
+                count_valid(BATCH.as_bytes(), strict, rules).unwrap(),
+                count_valid_passports(&batch, strict, rules) as usize
+            );
+        }
+        assert_eq!(count_valid(&b""[..], true, KeyRules::default()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_count_valid_parts() {
+        let rules = KeyRules::default();
+
+        assert_eq!(
+            count_valid_parts(BATCH.as_bytes(), rules).unwrap(),
+            (
+                count_valid(BATCH.as_bytes(), false, rules).unwrap(),
+                count_valid(BATCH.as_bytes(), true, rules).unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let input = b"byr:1937\n\niyr:2017\ncid:\xff\n";
+        let mut reader = BatchReader::new(&input[..]);
+
+        assert_eq!(reader.next_passport().unwrap(), Some(vec![("byr", "1937")]));
+        match reader.next_passport() {
+            Err(StreamError::InvalidUtf8 { line }) => assert_eq!(line, 4),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_missing_separator() {
+        let error = count_valid(
+            "byr:1937\n\niyr:2017 cid\n".as_bytes(),
+            false,
+            KeyRules::default(),
+        )
+        .unwrap_err();
+
+        match error {
+            StreamError::Record(error) => assert_eq!(
+                error,
+                RecordError::MissingSeparator {
+                    line: 3,
+                    field: "cid".to_owned()
+                }
+            ),
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+}
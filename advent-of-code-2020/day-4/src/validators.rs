@@ -0,0 +1,196 @@
+//! Checks for the hair color, eye color and passport ID values
+//!
+//! The regular expressions in `reference` are the definition of a valid value,
+//! and are used by default through the `regex-validators` feature. The
+//! hand-written checks compare bytes directly and are used instead when the
+//! `fast-validators` feature is enabled or `regex-validators` is not, which
+//! lets the `regex` and `lazy_static` crates be left out. Both must accept
+//! exactly the same values.
+
+/// Tests for a `#` followed by exactly six lowercase hex digits
+pub fn is_hair_color(value: &str) -> bool {
+    match value.as_bytes() {
+        [b'#', digits @ ..] => {
+            digits.len() == 6
+                && digits
+                    .iter()
+                    .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+        }
+        _ => false,
+    }
+}
+
+/// Tests for one of the seven eye color codes
+pub fn is_eye_color(value: &str) -> bool {
+    matches!(value, "amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth")
+}
+
+/// Tests for exactly nine digits, including leading zeros
+pub fn is_passport_id(value: &str) -> bool {
+    value.len() == 9 && value.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// The original checks, written as regular expressions
+#[cfg(any(test, feature = "regex-validators"))]
+pub mod reference {
+    use lazy_static::lazy_static;
+    use regex::Regex;
+
+    lazy_static! {
+        static ref HCL_REGEX: Regex = Regex::new(r"^#[a-f0-9]{6}$").unwrap();
+        static ref ECL_REGEX: Regex =
+            Regex::new(r"^amb$|^blu$|^brn$|^gry$|^grn$|^hzl$|^oth$").unwrap();
+        static ref PID_REGEX: Regex = Regex::new(r"^[0-9]{9}$").unwrap();
+    }
+
+    pub fn is_hair_color(value: &str) -> bool {
+        HCL_REGEX.is_match(value)
+    }
+
+    pub fn is_eye_color(value: &str) -> bool {
+        ECL_REGEX.is_match(value)
+    }
+
+    pub fn is_passport_id(value: &str) -> bool {
+        PID_REGEX.is_match(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    /// Characters that appear in valid values, their near misses, and characters
+    /// that regular expressions treat specially
+    const ALPHABET: &[char] = &[
+        '#', '0', '1', '5', '9', 'a', 'b', 'f', 'g', 'm', 'n', 'r', 'u', 'y', 'z', 'A', 'F', 'B',
+        ' ', '\n', '\r', '\t', '$', '^', '.', 'é', '٣',
+    ];
+
+    const VALID: &[&str] = &[
+        "#123abc",
+        "#000000",
+        "#ffffff",
+        "#a97842",
+        "amb",
+        "blu",
+        "brn",
+        "gry",
+        "grn",
+        "hzl",
+        "oth",
+        "000000001",
+        "087499704",
+        "999999999",
+    ];
+
+    /// Builds a value either at random or by changing a valid one slightly
+    fn generate_value(rng: &mut StdRng) -> String {
+        let mut value: Vec<char> = if rng.gen_bool(0.5) {
+            VALID.choose(rng).unwrap().chars().collect()
+        } else {
+            let length = rng.gen_range(0..=11);
+            (0..length)
+                .map(|_| *ALPHABET.choose(rng).unwrap())
+                .collect()
+        };
+
+        for _ in 0..rng.gen_range(0..=2) {
+            let c = *ALPHABET.choose(rng).unwrap();
+            let index = rng.gen_range(0..=value.len());
+
+            match rng.gen_range(0..3) {
+                0 => value.insert(index, c),
+                1 if index < value.len() => value[index] = c,
+                _ if index < value.len() => {
+                    value.remove(index);
+                }
+                _ => {}
+            }
+        }
+
+        value.into_iter().collect()
+    }
+
+    fn assert_agree(value: &str) {
+        assert_eq!(
+            is_hair_color(value),
+            reference::is_hair_color(value),
+            "hcl {:?}",
+            value
+        );
+        assert_eq!(
+            is_eye_color(value),
+            reference::is_eye_color(value),
+            "ecl {:?}",
+            value
+        );
+        assert_eq!(
+            is_passport_id(value),
+            reference::is_passport_id(value),
+            "pid {:?}",
+            value
+        );
+    }
+
+    #[test]
+    fn test_valid_values() {
+        for value in VALID {
+            assert!(is_hair_color(value) || is_eye_color(value) || is_passport_id(value));
+            assert_agree(value);
+        }
+    }
+
+    #[test]
+    fn test_edge_cases_agree() {
+        let values = [
+            "",
+            "#",
+            "#12345",
+            "#1234567",
+            "123abc",
+            "#123ABC",
+            "#123abg",
+            "#123abc\n",
+            "amb\n",
+            "AMB",
+            " amb",
+            "ambb",
+            "am",
+            "12345678",
+            "1234567890",
+            "00000000a",
+            "٣٣٣٣٣٣٣٣٣",
+            "#٣٣٣٣٣٣",
+            "0000000\u{0}0",
+        ];
+
+        for value in &values {
+            assert_agree(value);
+        }
+    }
+
+    #[test]
+    fn test_generated_values_agree() {
+        let mut rng = StdRng::seed_from_u64(0x2020_0004);
+        let mut accepted = 0;
+
+        for _ in 0..100_000 {
+            let value = generate_value(&mut rng);
+            assert_agree(&value);
+
+            if reference::is_hair_color(&value)
+                || reference::is_eye_color(&value)
+                || reference::is_passport_id(&value)
+            {
+                accepted += 1;
+            }
+        }
+
+        // Enough values are valid that acceptance is compared, not only rejection
+        assert!(accepted > 1_000);
+    }
+}
@@ -1,41 +1,28 @@
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-
-/// Creates an iterator over the lines of the puzzle input
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
-}
+use std::fs;
+use std::iter;
+
+use common::records::records;
 
 /// Parse the input into a vector of vector of chars
 fn parse_input(filename: &str) -> Vec<Vec<char>> {
-    let mut results = Vec::new();
-    let mut group = Vec::new();
-    match read_lines(filename) {
-        Ok(lines) => {
-            for line in lines {
-                let line = line.unwrap();
-
-                if line == "" {
-                    results.push(group.to_owned());
-                    group = Vec::new();
-                } else {
-                    group.extend(line.chars());
-                    group.push('\n');
-                }
-            }
-        },
+    match fs::read_to_string(filename) {
+        Ok(input) => parse_groups(&input),
         Err(e) => panic!("Error reading file: {}", e),
     }
+}
 
-    results.push(group.to_owned());
-
-    results
+/// Parses the text of the input, ending each response in a group with '\n'
+fn parse_groups(input: &str) -> Vec<Vec<char>> {
+    records(input)
+        .map(|record| {
+            record
+                .lines
+                .iter()
+                .flat_map(|line| line.chars().chain(iter::once('\n')))
+                .collect()
+        })
+        .collect()
 }
 
 /// This solution iterates over each group of responses
@@ -97,6 +84,13 @@ mod tests {
         ]
     }
 
+    #[test]
+    fn test_parse_groups() {
+        let input = "abc\r\n\r\na\r\nb\r\nc\r\n \r\nab\nac\n\t\n\n\na\na\na\na\n\nb\n\n";
+
+        assert_eq!(parse_groups(input), get_example_input());
+    }
+
     #[test]
     fn test_puzzle_one_example() {
         let input = get_example_input();